@@ -1,76 +1,173 @@
 //! 音频捕获模块
-//! 使用 cpal 实现 WASAPI loopback 捕获系统音频
+//! 使用 cpal 捕获系统音频或麦克风
+//!
+//! Linux 下通过 PulseAudio/PipeWire 的 monitor source 实现 loopback：
+//! 找到默认（或指定）sink 对应的 `<sink>.monitor`，再用 `parec` 直接录制。
 
+use crate::audio_source::{AudioReceiver, AudioSource, CaptureMode};
+use crate::error::AppError;
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{Device, Host, Sample, Stream, StreamConfig};
 use rubato::{FftFixedInOut, Resampler};
+#[cfg(target_os = "linux")]
+use std::io::Read;
+#[cfg(target_os = "linux")]
+use std::process::{Child, Command, Stdio};
+#[cfg(target_os = "linux")]
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, Mutex};
+#[cfg(target_os = "linux")]
+use std::thread::{self, JoinHandle};
 
 /// 音频捕获器
 pub struct AudioCapture {
     stream: Option<Stream>,
     sample_rate: u32,
     target_sample_rate: u32,
+    capture_mode: CaptureMode,
+    /// 设备 ID（Linux 系统音频模式下为 `output:{index}:{sink}` 或 sink 名称，None 表示使用默认设备）
+    device_id: Option<String>,
+    /// Linux 系统音频：录制 monitor source 的 parec 进程
+    #[cfg(target_os = "linux")]
+    monitor: Option<MonitorCapture>,
+    /// 使音频提前结束的错误 (例如 parec 意外退出)
+    error: Arc<Mutex<Option<String>>>,
 }
 
-/// 音频数据接收器
-pub type AudioReceiver = Receiver<Vec<f32>>;
-
 impl AudioCapture {
-    /// 创建音频捕获器
+    /// 创建指定设备的音频捕获器
     ///
     /// # Arguments
-    /// * `target_sample_rate` - 目标采样率 (通常为 16000)
-    pub fn new(target_sample_rate: u32) -> Self {
+    /// * `capture_mode` - 捕获模式
+    /// * `device_id` - `enumerate_audio_devices` 返回的设备 ID，None 表示默认设备
+    pub fn new(capture_mode: CaptureMode, device_id: Option<String>) -> Self {
         Self {
             stream: None,
             sample_rate: 0,
-            target_sample_rate,
+            target_sample_rate: 0,
+            capture_mode,
+            device_id,
+            #[cfg(target_os = "linux")]
+            monitor: None,
+            error: Arc::new(Mutex::new(None)),
         }
     }
 
+    /// 获取麦克风设备
+    ///
+    /// 设备 ID 格式为 `input:{index}:{name}`，优先按名称匹配，找不到时再按索引匹配，都不匹配时报错
+    fn get_input_device(device_id: Option<&str>) -> Result<(Host, Device, StreamConfig), String> {
+        let host = cpal::default_host();
+
+        let device = match device_id.and_then(parse_device_id) {
+            Some((index, name)) => {
+                let devices: Vec<Device> = host
+                    .devices()
+                    .map_err(|e| format!("无法枚举音频设备: {}", e))?
+                    .collect();
+
+                // 找不到选择的设备时报错，不悄悄改用默认设备
+                let position = devices
+                    .iter()
+                    .position(|d| {
+                        d.supports_input() && d.name().map(|n| n == name).unwrap_or(false)
+                    })
+                    .or_else(|| index.filter(|&i| i < devices.len() && devices[i].supports_input()))
+                    .ok_or_else(|| format!("找不到选择的输入设备: {}", name))?;
+                devices.into_iter().nth(position)
+            }
+            None => host.default_input_device(),
+        }
+        .ok_or_else(|| "没有找到输入设备".to_string())?;
+
+        eprintln!("Using input device: {:?}", device.name());
+
+        let supported_config = device
+            .default_input_config()
+            .map_err(|e| format!("无法获取输入设备配置: {}", e))?;
+
+        let config: StreamConfig = supported_config.into();
+        eprintln!("Audio config: {:?}", config);
+
+        Ok((host, device, config))
+    }
+
     /// 获取默认的 loopback 设备
     #[cfg(target_os = "windows")]
-    fn get_loopback_device() -> Result<(Host, Device, StreamConfig), String> {
+    fn get_loopback_device(
+        _sink_name: Option<&str>,
+    ) -> Result<(Host, Device, StreamConfig), String> {
         use cpal::SupportedStreamConfig;
 
         let host = cpal::host_from_id(cpal::HostId::Wasapi)
-            .map_err(|e| format!("Failed to get WASAPI host: {}", e))?;
+            .map_err(|e| format!("无法获取 WASAPI 音频接口: {}", e))?;
 
         // 获取默认输出设备
         let device = host
             .default_output_device()
-            .ok_or_else(|| "No default output device found".to_string())?;
+            .ok_or_else(|| "没有找到默认输出设备".to_string())?;
 
-        println!("Using output device for loopback: {:?}", device.name());
+        eprintln!("Using output device for loopback: {:?}", device.name());
 
         // 获取输出设备的配置
         let supported_config: SupportedStreamConfig = device
             .default_output_config()
-            .map_err(|e| format!("Failed to get output config: {}", e))?;
+            .map_err(|e| format!("无法获取输出设备配置: {}", e))?;
 
         let config: StreamConfig = supported_config.into();
-        println!("Audio config: {:?}", config);
+        eprintln!("Audio config: {:?}", config);
 
         Ok((host, device, config))
     }
 
-    #[cfg(not(target_os = "windows"))]
-    fn get_loopback_device() -> Result<(Host, Device, StreamConfig), String> {
-        Err("Loopback capture is only supported on Windows".to_string())
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    fn get_loopback_device(
+        _sink_name: Option<&str>,
+    ) -> Result<(Host, Device, StreamConfig), String> {
+        Err("仅 Windows 和 Linux 支持录制系统声音".to_string())
     }
 
-    /// 开始捕获音频
+    /// 录制默认（或指定）sink 的 monitor source
     ///
-    /// 返回一个接收器用于获取音频数据
-    pub fn start(&mut self) -> Result<AudioReceiver, String> {
-        let (_host, device, config) = Self::get_loopback_device()?;
+    /// ALSA 本身不暴露 monitor source，这里通过 `pactl` 找到 sink，
+    /// 再用 `parec --device=<sink>.monitor` 按模型采样率录制单声道 f32 音频，不需要重采样
+    #[cfg(target_os = "linux")]
+    fn start_monitor(&mut self) -> Result<AudioReceiver, String> {
+        let sink = match self.device_id.as_deref().and_then(parse_device_id) {
+            Some((_, name)) => name.to_string(),
+            None => default_sink_name()?,
+        };
+        let monitor = if sink.ends_with(".monitor") {
+            sink
+        } else {
+            format!("{}.monitor", sink)
+        };
+
+        eprintln!("Using monitor source for loopback: {}", monitor);
+
+        self.sample_rate = self.target_sample_rate;
+        let (capture, rx) =
+            MonitorCapture::start(&monitor, self.target_sample_rate, self.error.clone())?;
+        self.monitor = Some(capture);
+
+        Ok(rx)
+    }
+
+    /// 开始捕获音频，返回一个接收器用于获取音频数据
+    fn start_stream(&mut self) -> Result<AudioReceiver, String> {
+        let (_host, device, config) = match self.capture_mode {
+            #[cfg(target_os = "linux")]
+            CaptureMode::SystemAudio => return self.start_monitor(),
+            #[cfg(not(target_os = "linux"))]
+            CaptureMode::SystemAudio => Self::get_loopback_device(self.device_id.as_deref())?,
+            CaptureMode::Microphone => Self::get_input_device(self.device_id.as_deref())?,
+        };
 
         self.sample_rate = config.sample_rate.0;
         let channels = config.channels as usize;
 
-        println!(
+        eprintln!(
             "Source sample rate: {}, channels: {}",
             self.sample_rate, channels
         );
@@ -87,7 +184,7 @@ impl AudioCapture {
                     1024, // chunk size
                     1,    // mono
                 )
-                .map_err(|e| format!("Failed to create resampler: {}", e))?,
+                .map_err(|e| format!("无法创建重采样器: {}", e))?,
             )))
         } else {
             None
@@ -110,7 +207,7 @@ impl AudioCapture {
 
         stream
             .play()
-            .map_err(|e| format!("Failed to play stream: {}", e))?;
+            .map_err(|e| format!("无法启动音频流: {}", e))?;
         self.stream = Some(stream);
 
         Ok(rx)
@@ -172,19 +269,57 @@ impl AudioCapture {
                 },
                 None,
             )
-            .map_err(|e| format!("Failed to build input stream: {}", e))?;
+            .map_err(|e| format!("无法创建输入音频流: {}", e))?;
 
         Ok(stream)
     }
 
+    /// 是否正在捕获
+    pub fn is_capturing(&self) -> bool {
+        #[cfg(target_os = "linux")]
+        if self.monitor.is_some() {
+            return true;
+        }
+        self.stream.is_some()
+    }
+}
+
+impl AudioSource for AudioCapture {
+    fn start(&mut self, sample_rate: u32) -> Result<AudioReceiver, AppError> {
+        self.target_sample_rate = sample_rate;
+        self.start_stream()
+            .map_err(|message| AppError::AudioDevice {
+                device: self.device_id.clone(),
+                message,
+            })
+    }
+
     /// 停止捕获
-    pub fn stop(&mut self) {
+    fn stop(&mut self) {
         self.stream = None;
+        #[cfg(target_os = "linux")]
+        {
+            self.monitor = None;
+        }
     }
 
-    /// 是否正在捕获
-    pub fn is_capturing(&self) -> bool {
-        self.stream.is_some()
+    fn describe(&self) -> String {
+        let mode = match self.capture_mode {
+            CaptureMode::SystemAudio => "system audio",
+            CaptureMode::Microphone => "microphone",
+        };
+        match &self.device_id {
+            Some(device_id) => format!("{} ({})", mode, device_id),
+            None => format!("{} (default device)", mode),
+        }
+    }
+
+    fn take_error(&mut self) -> Option<AppError> {
+        let message = self.error.lock().unwrap().take()?;
+        Some(AppError::AudioDevice {
+            device: self.device_id.clone(),
+            message,
+        })
     }
 }
 
@@ -193,3 +328,161 @@ impl Drop for AudioCapture {
         self.stop();
     }
 }
+
+/// 录制 monitor source 的 parec 进程
+///
+/// 通过 `--device` 直接指定录制目标，不修改进程环境变量，也不影响之后的麦克风捕获
+#[cfg(target_os = "linux")]
+struct MonitorCapture {
+    child: Child,
+    reader: Option<JoinHandle<()>>,
+    stopping: Arc<AtomicBool>,
+}
+
+#[cfg(target_os = "linux")]
+impl MonitorCapture {
+    /// 每次发送的采样数
+    const CHUNK_SIZE: usize = 1024;
+
+    fn start(
+        monitor: &str,
+        sample_rate: u32,
+        error: Arc<Mutex<Option<String>>>,
+    ) -> Result<(Self, AudioReceiver), String> {
+        let mut child = Command::new("parec")
+            .arg(format!("--device={}", monitor))
+            .arg(format!("--rate={}", sample_rate))
+            .args(["--format=float32le", "--channels=1", "--latency-msec=50"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("无法启动 parec (需要安装 pulseaudio-utils): {}", e))?;
+
+        let mut stdout = child.stdout.take().expect("parec stdout is piped");
+        let mut stderr = child.stderr.take().expect("parec stderr is piped");
+        let stopping = Arc::new(AtomicBool::new(false));
+        let stopping_clone = stopping.clone();
+        let (tx, rx) = mpsc::channel();
+
+        *error.lock().unwrap() = None;
+
+        let reader = thread::spawn(move || {
+            let mut bytes = vec![0u8; Self::CHUNK_SIZE * 4];
+            while stdout.read_exact(&mut bytes).is_ok() {
+                let chunk: Vec<f32> = bytes
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect();
+                if tx.send(chunk).is_err() {
+                    return;
+                }
+            }
+
+            // 停止时 parec 被结束，输出关闭属于正常情况
+            if stopping_clone.load(Ordering::SeqCst) {
+                return;
+            }
+
+            let mut message = String::new();
+            let _ = stderr.read_to_string(&mut message);
+            let message = match message.trim() {
+                "" => "parec 意外退出".to_string(),
+                detail => format!("parec 意外退出: {}", detail),
+            };
+            eprintln!("Monitor capture error: {}", message);
+            // 先记录错误再关闭通道，识别引擎读到通道关闭时才能取到错误
+            *error.lock().unwrap() = Some(message);
+            drop(tx);
+        });
+
+        Ok((
+            Self {
+                child,
+                reader: Some(reader),
+                stopping,
+            },
+            rx,
+        ))
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for MonitorCapture {
+    fn drop(&mut self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+    }
+}
+
+/// 通过 pactl 列出所有 sink，返回 (索引, sink 名称)
+#[cfg(target_os = "linux")]
+pub fn list_sinks() -> Result<Vec<(u32, String)>, String> {
+    let output = Command::new("pactl")
+        .args(["list", "short", "sinks"])
+        .output()
+        .map_err(|e| format!("无法运行 pactl: {}", e))?;
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split('\t');
+            let index = columns.next()?.trim().parse().ok()?;
+            let name = columns.next()?.trim();
+            (!name.is_empty()).then(|| (index, name.to_string()))
+        })
+        .collect())
+}
+
+/// 通过 pactl 查询默认 sink 名称 (PulseAudio 与 pipewire-pulse 均适用)
+#[cfg(target_os = "linux")]
+pub fn default_sink_name() -> Result<String, String> {
+    // pactl >= 15 支持 get-default-sink
+    if let Ok(output) = Command::new("pactl").arg("get-default-sink").output() {
+        if output.status.success() {
+            let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if !name.is_empty() {
+                return Ok(name);
+            }
+        }
+    }
+
+    // 旧版本回退到解析 `pactl info`
+    let output = Command::new("pactl")
+        .arg("info")
+        .output()
+        .map_err(|e| format!("无法运行 pactl: {}", e))?;
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix("Default Sink:"))
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| "没有找到默认的 PulseAudio/PipeWire 输出设备".to_string())
+}
+
+/// 解析设备 ID，返回 (索引, 设备名称)
+///
+/// 支持 `input:{index}:{name}`、`{index}:{name}` 以及纯设备名称
+fn parse_device_id(device_id: &str) -> Option<(Option<usize>, &str)> {
+    let id = device_id
+        .strip_prefix("input:")
+        .or_else(|| device_id.strip_prefix("output:"))
+        .unwrap_or(device_id);
+
+    if id.is_empty() {
+        return None;
+    }
+
+    match id.split_once(':') {
+        Some((index, name)) => match index.parse::<usize>() {
+            Ok(index) => Some((Some(index), name)),
+            Err(_) => Some((None, id)),
+        },
+        None => Some((None, id)),
+    }
+}
@@ -0,0 +1,404 @@
+//! 音频文件输入模块
+//! 使用 symphonia 解码 WAV/FLAC/MP3/OGG 文件，输出与 `AudioCapture` 相同的单声道 f32 数据流
+
+use crate::audio_source::{AudioReceiver, AudioSource};
+use crate::error::AppError;
+use rubato::{FftFixedIn, Resampler};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// 每次发送的样本数 (目标采样率下)
+const CHUNK_SIZE: usize = 1024;
+
+/// 文件结束后补充的静音时长 (秒)，让流式模型输出最后几个字
+const TAIL_PADDING_SECS: f32 = 0.8;
+
+/// 文件播放速度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlaybackPace {
+    /// 按实际时长播放，与实时字幕表现一致
+    RealTime,
+    /// 尽可能快，由识别器的消费速度决定
+    AsFastAsPossible,
+}
+
+/// 音频文件输入源
+pub struct AudioFileSource {
+    path: PathBuf,
+    target_sample_rate: u32,
+    pace: PlaybackPace,
+    stop_flag: Arc<Mutex<bool>>,
+    pause_flag: Arc<Mutex<bool>>,
+    /// 解码线程遇到的错误 (在关闭通道之前写入)
+    error: Arc<Mutex<Option<String>>>,
+    decode_thread: Option<thread::JoinHandle<()>>,
+}
+
+impl AudioFileSource {
+    /// 创建音频文件输入源
+    ///
+    /// # Arguments
+    /// * `path` - 音频文件路径
+    /// * `pace` - 播放速度
+    pub fn new(path: impl Into<PathBuf>, pace: PlaybackPace) -> Self {
+        Self {
+            path: path.into(),
+            target_sample_rate: 0,
+            pace,
+            stop_flag: Arc::new(Mutex::new(false)),
+            pause_flag: Arc::new(Mutex::new(false)),
+            error: Arc::new(Mutex::new(None)),
+            decode_thread: None,
+        }
+    }
+}
+
+impl AudioSource for AudioFileSource {
+    /// 开始解码文件
+    ///
+    /// 文件打开和格式探测在当前线程完成，以便立即返回错误；
+    /// 解码在后台线程进行，文件读完后通道关闭
+    fn start(&mut self, sample_rate: u32) -> Result<AudioReceiver, AppError> {
+        self.target_sample_rate = sample_rate;
+        let opened = open_audio_file(&self.path).map_err(|message| AppError::AudioFile {
+            path: self.path.clone(),
+            message,
+        })?;
+
+        eprintln!(
+            "Decoding audio file: {} ({} Hz -> {} Hz, {:?})",
+            self.path.display(),
+            opened.source_sample_rate,
+            self.target_sample_rate,
+            self.pace
+        );
+
+        // 有界通道：快速模式下识别器处理不过来时会阻塞解码线程
+        let (tx, rx) = mpsc::sync_channel(32);
+        let stop_flag = self.stop_flag.clone();
+        let pause_flag = self.pause_flag.clone();
+        let error = self.error.clone();
+        let target_sample_rate = self.target_sample_rate;
+        let pace = self.pace;
+
+        *stop_flag.lock().unwrap() = false;
+        *pause_flag.lock().unwrap() = false;
+        *error.lock().unwrap() = None;
+
+        let handle = thread::spawn(move || {
+            let result = decode_loop(opened, target_sample_rate, pace, &tx, stop_flag, pause_flag);
+
+            // 先记录错误再关闭通道，识别引擎在通道关闭后检查
+            if let Err(e) = result {
+                eprintln!("Audio file decode error: {}", e);
+                *error.lock().unwrap() = Some(e);
+            }
+            drop(tx);
+        });
+
+        self.decode_thread = Some(handle);
+        Ok(rx)
+    }
+
+    /// 停止解码
+    fn stop(&mut self) {
+        *self.stop_flag.lock().unwrap() = true;
+        if let Some(handle) = self.decode_thread.take() {
+            let _ = handle.join();
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!("file {} ({:?})", self.path.display(), self.pace)
+    }
+
+    /// 暂停/恢复解码 (暂停期间不发送音频，实时模式的播放进度也随之推迟)
+    fn set_paused(&self, paused: bool) {
+        *self.pause_flag.lock().unwrap() = paused;
+    }
+
+    fn take_error(&mut self) -> Option<AppError> {
+        let message = self.error.lock().unwrap().take()?;
+        Some(AppError::AudioFile {
+            path: self.path.clone(),
+            message,
+        })
+    }
+}
+
+impl Drop for AudioFileSource {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// 已打开的音频文件
+struct OpenedAudioFile {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    source_sample_rate: u32,
+}
+
+/// 打开音频文件并创建解码器
+fn open_audio_file(path: &Path) -> Result<OpenedAudioFile, String> {
+    let file = File::open(path).map_err(|e| format!("无法打开音频文件: {}", e))?;
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            mss,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|e| format!("不支持的音频文件: {}", e))?;
+
+    let format = probed.format;
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| "文件中没有音轨".to_string())?;
+
+    let track_id = track.id;
+    let source_sample_rate = track
+        .codec_params
+        .sample_rate
+        .ok_or_else(|| "无法确定采样率".to_string())?;
+
+    let decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|e| format!("无法创建解码器: {}", e))?;
+
+    Ok(OpenedAudioFile {
+        format,
+        decoder,
+        track_id,
+        source_sample_rate,
+    })
+}
+
+/// 解码循环：解码 -> 混合为单声道 -> 重采样 -> 按块发送
+fn decode_loop(
+    opened: OpenedAudioFile,
+    target_sample_rate: u32,
+    pace: PlaybackPace,
+    tx: &SyncSender<Vec<f32>>,
+    stop_flag: Arc<Mutex<bool>>,
+    pause_flag: Arc<Mutex<bool>>,
+) -> Result<(), String> {
+    let OpenedAudioFile {
+        mut format,
+        mut decoder,
+        track_id,
+        source_sample_rate,
+    } = opened;
+
+    let mut resampler = if source_sample_rate != target_sample_rate {
+        Some(
+            FftFixedIn::<f32>::new(
+                source_sample_rate as usize,
+                target_sample_rate as usize,
+                CHUNK_SIZE,
+                2, // sub chunks
+                1, // mono channel
+            )
+            .map_err(|e| format!("无法创建重采样器: {}", e))?,
+        )
+    } else {
+        None
+    };
+
+    let mut pacer = Pacer::new(pace, target_sample_rate, pause_flag);
+    let mut mono_buffer: Vec<f32> = Vec::new();
+    let mut output_buffer: Vec<f32> = Vec::new();
+
+    loop {
+        if *stop_flag.lock().unwrap() {
+            return Ok(());
+        }
+
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            // 文件读取结束
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                break;
+            }
+            Err(e) => return Err(format!("无法读取音频数据: {}", e)),
+        };
+
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(SymphoniaError::DecodeError(e)) => {
+                // 单个损坏的包直接跳过
+                eprintln!("[AudioFile] Skipping corrupted packet: {}", e);
+                continue;
+            }
+            Err(e) => return Err(format!("无法解码音频数据: {}", e)),
+        };
+
+        let spec = *decoded.spec();
+        let channels = spec.channels.count().max(1);
+        let mut sample_buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        sample_buffer.copy_interleaved_ref(decoded);
+
+        // 转换为单声道
+        mono_buffer.extend(
+            sample_buffer
+                .samples()
+                .chunks(channels)
+                .map(|frame| frame.iter().sum::<f32>() / channels as f32),
+        );
+
+        resample_into(&mut resampler, &mut mono_buffer, &mut output_buffer, false)?;
+
+        if !send_chunks(&mut output_buffer, tx, &mut pacer, &stop_flag, false) {
+            return Ok(());
+        }
+    }
+
+    // 处理剩余样本并补充尾部静音
+    resample_into(&mut resampler, &mut mono_buffer, &mut output_buffer, true)?;
+    output_buffer.extend(std::iter::repeat_n(
+        0.0,
+        (target_sample_rate as f32 * TAIL_PADDING_SECS) as usize,
+    ));
+    send_chunks(&mut output_buffer, tx, &mut pacer, &stop_flag, true);
+
+    Ok(())
+}
+
+/// 将单声道缓冲区重采样到输出缓冲区
+///
+/// `flush` 为 true 时用静音补齐最后一个不完整的块
+fn resample_into(
+    resampler: &mut Option<FftFixedIn<f32>>,
+    input: &mut Vec<f32>,
+    output: &mut Vec<f32>,
+    flush: bool,
+) -> Result<(), String> {
+    let resampler = match resampler {
+        Some(resampler) => resampler,
+        None => {
+            output.append(input);
+            return Ok(());
+        }
+    };
+
+    loop {
+        let frames_needed = resampler.input_frames_next();
+        if input.len() < frames_needed {
+            if !flush || input.is_empty() {
+                return Ok(());
+            }
+            input.resize(frames_needed, 0.0);
+        }
+
+        let chunk: Vec<f32> = input.drain(..frames_needed).collect();
+        let resampled = resampler
+            .process(&[chunk], None)
+            .map_err(|e| format!("重采样出错: {}", e))?;
+        output.extend(resampled.into_iter().next().unwrap_or_default());
+    }
+}
+
+/// 按块发送输出缓冲区中的样本
+///
+/// 返回 false 表示已停止或接收端已关闭
+fn send_chunks(
+    output: &mut Vec<f32>,
+    tx: &SyncSender<Vec<f32>>,
+    pacer: &mut Pacer,
+    stop_flag: &Arc<Mutex<bool>>,
+    flush: bool,
+) -> bool {
+    while output.len() >= CHUNK_SIZE || (flush && !output.is_empty()) {
+        if *stop_flag.lock().unwrap() {
+            return false;
+        }
+
+        if !pacer.wait_while_paused(stop_flag) {
+            return false;
+        }
+
+        let len = output.len().min(CHUNK_SIZE);
+        let chunk: Vec<f32> = output.drain(..len).collect();
+
+        pacer.wait(chunk.len());
+        if tx.send(chunk).is_err() {
+            // 接收端已关闭
+            return false;
+        }
+    }
+
+    true
+}
+
+/// 控制发送节奏 (实时模式按时长发送，暂停时等待恢复)
+struct Pacer {
+    pace: PlaybackPace,
+    sample_rate: u32,
+    started: Instant,
+    samples_sent: u64,
+    pause_flag: Arc<Mutex<bool>>,
+}
+
+impl Pacer {
+    fn new(pace: PlaybackPace, sample_rate: u32, pause_flag: Arc<Mutex<bool>>) -> Self {
+        Self {
+            pace,
+            sample_rate,
+            started: Instant::now(),
+            samples_sent: 0,
+            pause_flag,
+        }
+    }
+
+    /// 暂停期间阻塞，返回 false 表示暂停时被停止
+    fn wait_while_paused(&mut self, stop_flag: &Arc<Mutex<bool>>) -> bool {
+        let paused_at = Instant::now();
+        while *self.pause_flag.lock().unwrap() {
+            if *stop_flag.lock().unwrap() {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        // 暂停的时长不计入播放进度
+        self.started += paused_at.elapsed();
+        true
+    }
+
+    /// 等待到这批样本应当"播放"的时刻
+    fn wait(&mut self, samples: usize) {
+        if self.pace == PlaybackPace::RealTime {
+            let due = Duration::from_secs_f64(self.samples_sent as f64 / self.sample_rate as f64);
+            let elapsed = self.started.elapsed();
+            if due > elapsed {
+                thread::sleep(due - elapsed);
+            }
+        }
+        self.samples_sent += samples as u64;
+    }
+}
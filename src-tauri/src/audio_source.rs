@@ -0,0 +1,321 @@
+//! 音频源抽象
+//!
+//! 识别引擎只依赖 `AudioSource`：系统音频/麦克风 (Linux/macOS 使用 cpal，Windows 使用 WASAPI)、
+//! 音频文件、调用方提供的通道，以及用于测试的合成音频 (静音或正弦波)。
+
+use crate::audio_file::PlaybackPace;
+use crate::error::AppError;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 音频数据接收器
+pub type AudioReceiver = Receiver<Vec<f32>>;
+
+/// 音频捕获模式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CaptureMode {
+    /// 系统音频 (Loopback)
+    SystemAudio,
+    /// 麦克风输入
+    Microphone,
+}
+
+/// 音频源：输出指定采样率的单声道 f32 音频
+pub trait AudioSource: Send {
+    /// 开始输出音频，返回音频接收器
+    ///
+    /// `sample_rate` 为识别模型的采样率，音频源负责重采样。
+    /// 有限长度的音频源 (文件、合成音频) 输出完毕后关闭通道。
+    /// 设备或文件无法打开时返回 `AppError::AudioDevice` / `AppError::AudioFile`
+    fn start(&mut self, sample_rate: u32) -> Result<AudioReceiver, AppError>;
+
+    /// 停止输出音频
+    fn stop(&mut self);
+
+    /// 音频源描述 (用于日志)
+    fn describe(&self) -> String;
+
+    /// 暂停/恢复输出
+    ///
+    /// 默认不处理：实时音频源暂停期间的音频由识别引擎丢弃，
+    /// 有限长度的音频源应暂停输出，避免暂停期间的音频丢失
+    fn set_paused(&self, _paused: bool) {}
+
+    /// 取出使音频提前结束的错误 (例如文件读到一半无法解码)
+    ///
+    /// 通道关闭后由识别引擎调用：有错误时识别以 `RecognitionState::Error` 结束，而不是正常完成
+    fn take_error(&mut self) -> Option<AppError> {
+        None
+    }
+}
+
+impl<S: AudioSource + ?Sized> AudioSource for Box<S> {
+    fn start(&mut self, sample_rate: u32) -> Result<AudioReceiver, AppError> {
+        (**self).start(sample_rate)
+    }
+
+    fn stop(&mut self) {
+        (**self).stop()
+    }
+
+    fn describe(&self) -> String {
+        (**self).describe()
+    }
+
+    fn set_paused(&self, paused: bool) {
+        (**self).set_paused(paused)
+    }
+
+    fn take_error(&mut self) -> Option<AppError> {
+        (**self).take_error()
+    }
+}
+
+/// 调用方提供的音频 (采样率必须与模型相同)，发送端关闭表示音频结束
+pub struct ChannelSource {
+    receiver: Option<AudioReceiver>,
+}
+
+impl ChannelSource {
+    pub fn new(receiver: AudioReceiver) -> Self {
+        Self {
+            receiver: Some(receiver),
+        }
+    }
+}
+
+impl AudioSource for ChannelSource {
+    fn start(&mut self, _sample_rate: u32) -> Result<AudioReceiver, AppError> {
+        self.receiver
+            .take()
+            .ok_or_else(|| AppError::InvalidState("通道音频源只能启动一次".to_string()))
+    }
+
+    /// 通道由调用方关闭
+    fn stop(&mut self) {}
+
+    fn describe(&self) -> String {
+        "channel".to_string()
+    }
+}
+
+/// 合成音频的信号
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Signal {
+    /// 静音
+    Silence,
+    /// 正弦波
+    Tone { frequency: f32, amplitude: f32 },
+}
+
+/// 合成音频源 (静音或正弦波)，用于在没有音频设备时测试识别流程
+pub struct SyntheticSource {
+    signal: Signal,
+    /// 总时长，None 表示持续输出直到停止
+    duration: Option<Duration>,
+    pace: PlaybackPace,
+    /// 每次发送的时长
+    chunk_duration: Duration,
+    stop_flag: Arc<AtomicBool>,
+    pause_flag: Arc<AtomicBool>,
+    generate_thread: Option<thread::JoinHandle<()>>,
+}
+
+impl SyntheticSource {
+    /// 创建合成音频源，默认尽快输出，每次发送 100ms
+    pub fn new(signal: Signal, duration: Option<Duration>) -> Self {
+        Self {
+            signal,
+            duration,
+            pace: PlaybackPace::AsFastAsPossible,
+            chunk_duration: Duration::from_millis(100),
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            pause_flag: Arc::new(AtomicBool::new(false)),
+            generate_thread: None,
+        }
+    }
+
+    /// 指定时长的静音
+    pub fn silence(duration: Duration) -> Self {
+        Self::new(Signal::Silence, Some(duration))
+    }
+
+    /// 指定时长的正弦波
+    pub fn tone(frequency: f32, amplitude: f32, duration: Duration) -> Self {
+        Self::new(
+            Signal::Tone {
+                frequency,
+                amplitude,
+            },
+            Some(duration),
+        )
+    }
+
+    /// 设置输出速度
+    pub fn with_pace(mut self, pace: PlaybackPace) -> Self {
+        self.pace = pace;
+        self
+    }
+
+    /// 设置每次发送的时长
+    pub fn with_chunk_duration(mut self, chunk_duration: Duration) -> Self {
+        self.chunk_duration = chunk_duration;
+        self
+    }
+}
+
+impl AudioSource for SyntheticSource {
+    fn start(&mut self, sample_rate: u32) -> Result<AudioReceiver, AppError> {
+        let chunk_size = (sample_rate as f64 * self.chunk_duration.as_secs_f64()) as usize;
+        if chunk_size == 0 {
+            return Err(AppError::Config("合成音频源的分块时长太短".to_string()));
+        }
+        let total = self
+            .duration
+            .map(|duration| (sample_rate as f64 * duration.as_secs_f64()) as u64);
+
+        // 有界通道：尽快输出时由识别器的消费速度决定
+        let (tx, rx) = mpsc::sync_channel(32);
+        let signal = self.signal;
+        let pace = self.pace;
+        let chunk_duration = self.chunk_duration;
+        let stop_flag = self.stop_flag.clone();
+        let pause_flag = self.pause_flag.clone();
+        stop_flag.store(false, Ordering::SeqCst);
+        pause_flag.store(false, Ordering::SeqCst);
+
+        let handle = thread::spawn(move || {
+            let mut generated: u64 = 0;
+            let mut next_send = Instant::now();
+
+            while !stop_flag.load(Ordering::SeqCst) {
+                if pause_flag.load(Ordering::SeqCst) {
+                    thread::sleep(Duration::from_millis(10));
+                    next_send = Instant::now();
+                    continue;
+                }
+
+                let len = match total {
+                    Some(total) if generated >= total => break,
+                    Some(total) => chunk_size.min((total - generated) as usize),
+                    None => chunk_size,
+                };
+                let chunk = (0..len as u64)
+                    .map(|i| signal.sample(generated + i, sample_rate))
+                    .collect();
+                generated += len as u64;
+
+                if pace == PlaybackPace::RealTime {
+                    next_send += chunk_duration;
+                    thread::sleep(next_send.saturating_duration_since(Instant::now()));
+                }
+                if tx.send(chunk).is_err() {
+                    break;
+                }
+            }
+        });
+
+        self.generate_thread = Some(handle);
+        Ok(rx)
+    }
+
+    fn stop(&mut self) {
+        self.stop_flag.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.generate_thread.take() {
+            let _ = handle.join();
+        }
+    }
+
+    fn describe(&self) -> String {
+        let duration = match self.duration {
+            Some(duration) => format!("{:.1}s", duration.as_secs_f32()),
+            None => "continuous".to_string(),
+        };
+        match self.signal {
+            Signal::Silence => format!("silence ({})", duration),
+            Signal::Tone { frequency, .. } => format!("{} Hz tone ({})", frequency, duration),
+        }
+    }
+
+    fn set_paused(&self, paused: bool) {
+        self.pause_flag.store(paused, Ordering::SeqCst);
+    }
+}
+
+impl Drop for SyntheticSource {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl Signal {
+    /// 第 `index` 个样本
+    fn sample(&self, index: u64, sample_rate: u32) -> f32 {
+        match *self {
+            Signal::Silence => 0.0,
+            Signal::Tone {
+                frequency,
+                amplitude,
+            } => {
+                let t = index as f64 / sample_rate as f64;
+                amplitude * (2.0 * std::f64::consts::PI * frequency as f64 * t).sin() as f32
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthetic_source_length() {
+        let mut source = SyntheticSource::silence(Duration::from_millis(250));
+        let rx = source.start(16000).unwrap();
+        let chunks: Vec<Vec<f32>> = rx.iter().collect();
+
+        assert_eq!(
+            chunks.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![1600, 1600, 800]
+        );
+        assert!(chunks.iter().flatten().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_synthetic_tone() {
+        let mut source = SyntheticSource::tone(1000.0, 0.5, Duration::from_millis(100));
+        let samples: Vec<f32> = source.start(8000).unwrap().iter().flatten().collect();
+
+        assert_eq!(samples.len(), 800);
+        // 8kHz 下 1kHz 正弦波每周期 8 个样本，第 2 个样本为峰值
+        assert!(samples[0].abs() < 1e-6);
+        assert!((samples[2] - 0.5).abs() < 1e-6);
+        assert!((samples[10] - 0.5).abs() < 1e-4);
+        assert!(samples.iter().all(|s| s.abs() <= 0.5 + 1e-6));
+    }
+
+    #[test]
+    fn test_continuous_source_stops() {
+        let mut source = SyntheticSource::new(Signal::Silence, None);
+        let rx = source.start(16000).unwrap();
+        assert_eq!(rx.recv().unwrap().len(), 1600);
+
+        // 停止后通道关闭
+        let handle = thread::spawn(move || rx.iter().count());
+        source.stop();
+        assert!(handle.join().is_ok());
+    }
+
+    #[test]
+    fn test_channel_source_starts_once() {
+        let (tx, rx) = mpsc::channel();
+        let mut source = ChannelSource::new(rx);
+        tx.send(vec![0.1, 0.2]).unwrap();
+
+        assert_eq!(source.start(16000).unwrap().recv().unwrap(), vec![0.1, 0.2]);
+        assert!(source.start(16000).is_err());
+    }
+}
@@ -1,74 +1,71 @@
 //! WASAPI Loopback 音频捕获模块
 //! 使用 Windows Audio Session API 捕获系统音频
 
+use crate::audio_source::{AudioReceiver, AudioSource, CaptureMode};
+use crate::error::AppError;
 use rubato::{FftFixedIn, Resampler};
-use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::mpsc::{self, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use windows::Win32::Media::Audio::*;
 use windows::Win32::System::Com::*;
 
-/// 音频捕获模式
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum CaptureMode {
-    /// 系统音频 (Loopback)
-    SystemAudio,
-    /// 麦克风输入
-    Microphone,
-}
-
 /// 音频捕获器
 pub struct AudioCapture {
     stop_flag: Arc<Mutex<bool>>,
     capture_thread: Option<thread::JoinHandle<()>>,
+    /// 捕获线程出错结束时的错误信息
+    error: Arc<Mutex<Option<String>>>,
     target_sample_rate: u32,
     capture_mode: CaptureMode,
     device_id: Option<String>,
 }
 
-/// 音频数据接收器
-pub type AudioReceiver = Receiver<Vec<f32>>;
-
 impl AudioCapture {
     /// 创建指定设备的音频捕获器
-    pub fn new_with_device(
-        target_sample_rate: u32,
-        capture_mode: CaptureMode,
-        device_id: Option<String>,
-    ) -> Self {
+    pub fn new(capture_mode: CaptureMode, device_id: Option<String>) -> Self {
         Self {
             stop_flag: Arc::new(Mutex::new(false)),
             capture_thread: None,
-            target_sample_rate,
+            error: Arc::new(Mutex::new(None)),
+            target_sample_rate: 0,
             capture_mode,
             device_id,
         }
     }
+}
 
+impl AudioSource for AudioCapture {
     /// 开始捕获音频
-    pub fn start(&mut self) -> Result<AudioReceiver, String> {
+    fn start(&mut self, sample_rate: u32) -> Result<AudioReceiver, AppError> {
+        self.target_sample_rate = sample_rate;
         let (tx, rx) = mpsc::channel();
         let stop_flag = self.stop_flag.clone();
         let target_sample_rate = self.target_sample_rate;
         let capture_mode = self.capture_mode;
         let device_id = self.device_id.clone();
+        let error = self.error.clone();
 
-        // 重置停止标志
+        // 重置停止标志和上次的错误
         *stop_flag.lock().unwrap() = false;
+        *error.lock().unwrap() = None;
 
         let handle = thread::spawn(move || {
             let result = match capture_mode {
                 CaptureMode::SystemAudio => {
-                    capture_loopback_audio(tx, stop_flag, target_sample_rate, device_id)
+                    capture_loopback_audio(&tx, stop_flag, target_sample_rate, device_id)
                 }
                 CaptureMode::Microphone => {
-                    capture_microphone_audio(tx, stop_flag, target_sample_rate, device_id)
+                    capture_microphone_audio(&tx, stop_flag, target_sample_rate, device_id)
                 }
             };
 
+            // 先保存错误再关闭通道，识别引擎在通道关闭后取出错误
             if let Err(e) = result {
                 eprintln!("Audio capture error: {}", e);
+                *error.lock().unwrap() = Some(e);
             }
+            drop(tx);
         });
 
         self.capture_thread = Some(handle);
@@ -76,12 +73,31 @@ impl AudioCapture {
     }
 
     /// 停止捕获
-    pub fn stop(&mut self) {
+    fn stop(&mut self) {
         *self.stop_flag.lock().unwrap() = true;
         if let Some(handle) = self.capture_thread.take() {
             let _ = handle.join();
         }
     }
+
+    fn describe(&self) -> String {
+        let mode = match self.capture_mode {
+            CaptureMode::SystemAudio => "system audio (WASAPI loopback)",
+            CaptureMode::Microphone => "microphone (WASAPI)",
+        };
+        match &self.device_id {
+            Some(device_id) => format!("{} ({})", mode, device_id),
+            None => format!("{} (default device)", mode),
+        }
+    }
+
+    fn take_error(&mut self) -> Option<AppError> {
+        let message = self.error.lock().unwrap().take()?;
+        Some(AppError::AudioDevice {
+            device: self.device_id.clone(),
+            message,
+        })
+    }
 }
 
 impl Drop for AudioCapture {
@@ -92,7 +108,7 @@ impl Drop for AudioCapture {
 
 /// WASAPI Loopback 捕获实现（系统音频）
 fn capture_loopback_audio(
-    tx: Sender<Vec<f32>>,
+    tx: &Sender<Vec<f32>>,
     stop_flag: Arc<Mutex<bool>>,
     target_sample_rate: u32,
     _device_id: Option<String>, // TODO: 支持选择特定设备
@@ -101,27 +117,27 @@ fn capture_loopback_audio(
         // 初始化 COM
         CoInitializeEx(Some(std::ptr::null()), COINIT_MULTITHREADED)
             .ok()
-            .map_err(|e| format!("Failed to initialize COM: {:?}", e))?;
+            .map_err(|e| format!("无法初始化 COM: {:?}", e))?;
 
         // 获取音频设备枚举器
         let enumerator: IMMDeviceEnumerator =
             CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)
-                .map_err(|e| format!("Failed to create device enumerator: {}", e))?;
+                .map_err(|e| format!("无法创建设备枚举器: {}", e))?;
 
         // 获取默认的音频渲染设备（用于 loopback）
         let device = enumerator
             .GetDefaultAudioEndpoint(eRender, eConsole)
-            .map_err(|e| format!("Failed to get default audio endpoint: {}", e))?;
+            .map_err(|e| format!("无法获取默认输出设备: {}", e))?;
 
         // 激活音频客户端
         let audio_client: IAudioClient = device
             .Activate(CLSCTX_ALL, None)
-            .map_err(|e| format!("Failed to activate audio client: {}", e))?;
+            .map_err(|e| format!("无法激活音频客户端: {}", e))?;
 
         // 获取混合格式
         let mix_format_ptr = audio_client
             .GetMixFormat()
-            .map_err(|e| format!("Failed to get mix format: {}", e))?;
+            .map_err(|e| format!("无法获取音频格式: {}", e))?;
 
         let mix_format = &*mix_format_ptr;
         let source_sample_rate = mix_format.nSamplesPerSec;
@@ -139,17 +155,17 @@ fn capture_loopback_audio(
                 mix_format_ptr,
                 None,
             )
-            .map_err(|e| format!("Failed to initialize audio client: {}", e))?;
+            .map_err(|e| format!("无法初始化音频客户端: {}", e))?;
 
         // 获取捕获客户端
         let capture_client: IAudioCaptureClient = audio_client
             .GetService()
-            .map_err(|e| format!("Failed to get capture client: {}", e))?;
+            .map_err(|e| format!("无法获取录音客户端: {}", e))?;
 
         // 启动捕获
         audio_client
             .Start()
-            .map_err(|e| format!("Failed to start audio client: {}", e))?;
+            .map_err(|e| format!("无法启动音频客户端: {}", e))?;
 
         // 创建重采样器
         let resampler = if source_sample_rate != target_sample_rate {
@@ -161,7 +177,7 @@ fn capture_loopback_audio(
                 2,    // sub chunks
                 1,    // mono channel
             )
-            .map_err(|e| format!("Failed to create resampler: {}", e))?;
+            .map_err(|e| format!("无法创建重采样器: {}", e))?;
 
             Some(Mutex::new(resampler))
         } else {
@@ -278,28 +294,28 @@ fn capture_loopback_audio(
 
 /// WASAPI 麦克风捕获实现
 fn capture_microphone_audio(
-    tx: Sender<Vec<f32>>,
+    tx: &Sender<Vec<f32>>,
     stop_flag: Arc<Mutex<bool>>,
     target_sample_rate: u32,
     _device_id: Option<String>, // TODO: 支持选择特定设备
 ) -> Result<(), String> {
     unsafe {
-        println!("[Microphone] Starting microphone capture...");
+        eprintln!("[Microphone] Starting microphone capture...");
 
         // 初始化 COM
         CoInitializeEx(Some(std::ptr::null()), COINIT_MULTITHREADED)
             .ok()
-            .map_err(|e| format!("Failed to initialize COM: {:?}", e))?;
+            .map_err(|e| format!("无法初始化 COM: {:?}", e))?;
 
         // 获取音频设备枚举器
         let enumerator: IMMDeviceEnumerator =
             CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)
-                .map_err(|e| format!("Failed to create device enumerator: {}", e))?;
+                .map_err(|e| format!("无法创建设备枚举器: {}", e))?;
 
         // 枚举所有活动的捕获设备（用于调试）
         // if let Ok(collection) = enumerator.EnumAudioEndpoints(eCapture, DEVICE_STATE_ACTIVE) {
         //     if let Ok(count) = collection.GetCount() {
-        //         println!("[Microphone] Found {} active capture devices", count);
+        //         eprintln!("[Microphone] Found {} active capture devices", count);
         //     }
         // }
 
@@ -307,26 +323,26 @@ fn capture_microphone_audio(
         let device = enumerator
             .GetDefaultAudioEndpoint(eCapture, eConsole)
             .or_else(|_| {
-                println!("[Microphone] Failed to get console device, trying communications");
+                eprintln!("[Microphone] Failed to get console device, trying communications");
                 enumerator.GetDefaultAudioEndpoint(eCapture, eCommunications)
             })
-            .map_err(|e| format!("Failed to get default capture endpoint: {}", e))?;
+            .map_err(|e| format!("无法获取默认输入设备: {}", e))?;
 
         // 激活音频客户端
         let audio_client: IAudioClient = device
             .Activate(CLSCTX_ALL, None)
-            .map_err(|e| format!("Failed to activate audio client: {}", e))?;
+            .map_err(|e| format!("无法激活音频客户端: {}", e))?;
 
         let mix_format_ptr = audio_client
             .GetMixFormat()
-            .map_err(|e| format!("Failed to get mix format: {}", e))?;
+            .map_err(|e| format!("无法获取音频格式: {}", e))?;
 
         let mix_format = &*mix_format_ptr;
         let source_sample_rate = mix_format.nSamplesPerSec;
         let channels = mix_format.nChannels as usize;
         let bits_per_sample = mix_format.wBitsPerSample;
 
-        println!(
+        eprintln!(
             "[Microphone] Format: {} Hz, {} ch, {} bit",
             source_sample_rate, channels, bits_per_sample
         );
@@ -355,18 +371,18 @@ fn capture_microphone_audio(
                     mix_format_ptr,
                     None,
                 )
-                .map_err(|e| format!("Failed to initialize audio client: {}", e))?;
+                .map_err(|e| format!("无法初始化音频客户端: {}", e))?;
         }
 
         // 获取捕获客户端
         let capture_client: IAudioCaptureClient = audio_client
             .GetService()
-            .map_err(|e| format!("Failed to get capture client: {}", e))?;
+            .map_err(|e| format!("无法获取录音客户端: {}", e))?;
 
         // 启动捕获
         audio_client
             .Start()
-            .map_err(|e| format!("Failed to start audio client: {}", e))?;
+            .map_err(|e| format!("无法启动音频客户端: {}", e))?;
 
         // 创建重采样器
         let resampler = if source_sample_rate != target_sample_rate {
@@ -377,7 +393,7 @@ fn capture_microphone_audio(
                 2,
                 1,
             )
-            .map_err(|e| format!("Failed to create resampler: {}", e))?;
+            .map_err(|e| format!("无法创建重采样器: {}", e))?;
 
             Some(Mutex::new(resampler))
         } else {
@@ -0,0 +1,332 @@
+//! 字幕自动保存
+//!
+//! 每次识别创建一个会话，最终结果逐行追加到 `session-<id>.jsonl`，
+//! 每行写入后立即落盘，应用崩溃或退出时已识别的文本不会丢失。
+//! 文件超过大小上限后写入 `session-<id>.<n>.jsonl`，旧会话按保留策略清理。
+
+use crate::config::AutosaveConfig;
+use crate::error::AppError;
+use crate::transcript::TranscriptEntry;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const FILE_PREFIX: &str = "session-";
+const FILE_EXTENSION: &str = "jsonl";
+
+/// 保存的一行记录
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SavedEntry {
+    /// 音频内的开始/结束时间与文本
+    #[serde(flatten)]
+    pub entry: TranscriptEntry,
+    /// 识别出该句的时间 (Unix 毫秒)
+    pub timestamp: u64,
+}
+
+/// 已保存的会话
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionInfo {
+    /// 会话 ID (开始时间的 Unix 毫秒)
+    pub id: String,
+    /// 开始时间 (Unix 毫秒)
+    pub started_at: u64,
+    /// 最后写入时间 (Unix 毫秒)
+    pub modified_at: u64,
+    /// 所有文件的总大小 (字节)
+    pub size: u64,
+    /// 文件列表 (按写入顺序)
+    pub files: Vec<String>,
+}
+
+/// 当前会话的写入器
+pub struct AutosaveWriter {
+    dir: PathBuf,
+    session_id: String,
+    part: u32,
+    file: File,
+    size: u64,
+    max_size: u64,
+}
+
+impl AutosaveWriter {
+    /// 创建新会话，同时按保留策略清理旧会话
+    pub fn start(config: &AutosaveConfig, dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+
+        // 同一毫秒内开始的会话顺延 ID，避免覆盖
+        let mut started_at = now_millis();
+        while part_path(dir, &started_at.to_string(), 0).exists() {
+            started_at += 1;
+        }
+        let session_id = started_at.to_string();
+        let file = create_part(dir, &session_id, 0)?;
+
+        if let Err(e) = apply_retention(dir, config.retention_days, config.max_sessions) {
+            eprintln!("[Autosave] Failed to clean up old sessions: {}", e);
+        }
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            session_id,
+            part: 0,
+            file,
+            size: 0,
+            max_size: config.max_file_size_kb.saturating_mul(1024),
+        })
+    }
+
+    /// 当前会话 ID
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// 追加一条最终结果
+    pub fn append(&mut self, entry: &TranscriptEntry) -> io::Result<()> {
+        let saved = SavedEntry {
+            entry: entry.clone(),
+            timestamp: now_millis(),
+        };
+        let mut line = serde_json::to_string(&saved)?;
+        line.push('\n');
+
+        // 当前文件写满后切换到下一个文件 (单行超过上限时仍写入空文件)
+        if self.size > 0 && self.size + line.len() as u64 > self.max_size {
+            self.part += 1;
+            self.file = create_part(&self.dir, &self.session_id, self.part)?;
+            self.size = 0;
+        }
+
+        self.file.write_all(line.as_bytes())?;
+        self.file.flush()?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+}
+
+/// 列出目录中保存的会话 (最新的在前)
+pub fn list_sessions(dir: &Path) -> io::Result<Vec<SessionInfo>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut sessions: Vec<SessionInfo> = Vec::new();
+    let mut parts: Vec<(String, u32, String)> = Vec::new();
+
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let Some((id, part)) = parse_file_name(&file_name) else {
+            continue;
+        };
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let modified_at = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or(0);
+
+        match sessions.iter_mut().find(|session| session.id == id) {
+            Some(session) => {
+                session.size += metadata.len();
+                session.modified_at = session.modified_at.max(modified_at);
+            }
+            None => sessions.push(SessionInfo {
+                started_at: id.parse().unwrap_or(0),
+                id: id.clone(),
+                modified_at,
+                size: metadata.len(),
+                files: Vec::new(),
+            }),
+        }
+        parts.push((id, part, file_name));
+    }
+
+    parts.sort_by_key(|(_, part, _)| *part);
+    for (id, _, file_name) in parts {
+        if let Some(session) = sessions.iter_mut().find(|session| session.id == id) {
+            session.files.push(file_name);
+        }
+    }
+
+    sessions.sort_by_key(|session| std::cmp::Reverse(session.started_at));
+    Ok(sessions)
+}
+
+/// 读取一个会话的全部记录
+///
+/// 崩溃时最后一行可能只写了一半，无法解析的行会被跳过
+pub fn load_session(dir: &Path, session_id: &str) -> Result<Vec<TranscriptEntry>, AppError> {
+    let sessions = list_sessions(dir).map_err(|e| AppError::io(dir, e))?;
+    let session = sessions
+        .iter()
+        .find(|session| session.id == session_id)
+        .ok_or_else(|| AppError::NotFound(format!("找不到字幕记录: {}", session_id)))?;
+
+    let mut entries = Vec::new();
+    for file_name in &session.files {
+        let path = dir.join(file_name);
+        let file = File::open(&path).map_err(|e| AppError::io(&path, e))?;
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| AppError::io(&path, e))?;
+            match serde_json::from_str::<SavedEntry>(&line) {
+                Ok(saved) => entries.push(saved.entry),
+                Err(e) if !line.trim().is_empty() => {
+                    eprintln!("[Autosave] Skipping invalid line in {}: {}", file_name, e);
+                }
+                Err(_) => {}
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// 删除超过保留天数或超出数量上限的会话 (0 表示不限制)
+pub fn apply_retention(dir: &Path, retention_days: u32, max_sessions: usize) -> io::Result<()> {
+    let sessions = list_sessions(dir)?;
+    let min_modified_at =
+        now_millis().saturating_sub(u64::from(retention_days) * 24 * 60 * 60 * 1000);
+
+    for (index, session) in sessions.iter().enumerate() {
+        let expired = retention_days > 0 && session.modified_at < min_modified_at;
+        let over_limit = max_sessions > 0 && index >= max_sessions;
+        if expired || over_limit {
+            for file_name in &session.files {
+                fs::remove_file(dir.join(file_name))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 解析文件名，返回 (会话 ID, 文件序号)
+fn parse_file_name(file_name: &str) -> Option<(String, u32)> {
+    let stem = file_name
+        .strip_prefix(FILE_PREFIX)?
+        .strip_suffix(FILE_EXTENSION)?
+        .strip_suffix('.')?;
+    let (id, part) = match stem.split_once('.') {
+        Some((id, part)) => (id, part.parse().ok()?),
+        None => (stem, 0),
+    };
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some((id.to_string(), part))
+}
+
+/// 会话文件路径，第一个文件不带序号
+fn part_path(dir: &Path, session_id: &str, part: u32) -> PathBuf {
+    if part == 0 {
+        dir.join(format!("{}{}.{}", FILE_PREFIX, session_id, FILE_EXTENSION))
+    } else {
+        dir.join(format!(
+            "{}{}.{}.{}",
+            FILE_PREFIX, session_id, part, FILE_EXTENSION
+        ))
+    }
+}
+
+fn create_part(dir: &Path, session_id: &str, part: u32) -> io::Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(part_path(dir, session_id, part))
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn entry(start: f32, text: &str) -> TranscriptEntry {
+        TranscriptEntry {
+            start,
+            end: start + 1.0,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_file_name() {
+        assert_eq!(
+            parse_file_name("session-1700000000000.jsonl"),
+            Some(("1700000000000".to_string(), 0))
+        );
+        assert_eq!(
+            parse_file_name("session-1700000000000.2.jsonl"),
+            Some(("1700000000000".to_string(), 2))
+        );
+        assert_eq!(parse_file_name("session-abc.jsonl"), None);
+        assert_eq!(parse_file_name("notes.jsonl"), None);
+    }
+
+    #[test]
+    fn test_rotation_and_reload() {
+        let dir = TempDir::new("autosave");
+
+        let config = AutosaveConfig {
+            enabled: true,
+            max_file_size_kb: 1,
+            ..Default::default()
+        };
+        let mut writer = AutosaveWriter::start(&config, &dir).unwrap();
+        let entries: Vec<_> = (0..40)
+            .map(|i| entry(i as f32, &format!("第 {} 句字幕", i)))
+            .collect();
+        for entry in &entries {
+            writer.append(entry).unwrap();
+        }
+
+        let sessions = list_sessions(&dir).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, writer.session_id());
+        assert!(sessions[0].files.len() > 1);
+
+        // 模拟崩溃时写了一半的行
+        let last_file = dir.join(sessions[0].files.last().unwrap());
+        let mut file = OpenOptions::new().append(true).open(last_file).unwrap();
+        file.write_all(b"{\"start\":41.0,\"te").unwrap();
+
+        assert_eq!(load_session(&dir, writer.session_id()).unwrap(), entries);
+        assert!(matches!(
+            load_session(&dir, "1"),
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_retention_keeps_newest_sessions() {
+        let dir = TempDir::new("autosave-retention");
+
+        let config = AutosaveConfig::default();
+        let ids: Vec<String> = (0..3)
+            .map(|_| {
+                let mut writer = AutosaveWriter::start(&config, &dir).unwrap();
+                writer.append(&entry(0.0, "hello")).unwrap();
+                writer.session_id().to_string()
+            })
+            .collect();
+
+        apply_retention(&dir, 0, 2).unwrap();
+        let remaining: Vec<String> = list_sessions(&dir)
+            .unwrap()
+            .into_iter()
+            .map(|session| session.id)
+            .collect();
+        assert_eq!(remaining, vec![ids[2].clone(), ids[1].clone()]);
+    }
+}
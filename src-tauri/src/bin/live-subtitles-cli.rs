@@ -0,0 +1,394 @@
+//! 实时字幕命令行工具
+//!
+//! 不依赖图形界面，从系统音频、麦克风、音频文件或标准输入 (PCM) 识别字幕，
+//! 以纯文本、JSONL 或 SRT 格式输出，适合在服务器、脚本和 CI 中使用。
+//! 诊断日志输出到标准错误，标准输出只包含字幕。
+//!
+//! ```text
+//! live-subtitles-cli --model-dir models/zipformer --source file --input talk.wav --format srt
+//! arecord -f S16_LE -r 16000 -c 1 -t raw | live-subtitles-cli --model-dir models/zipformer --source stdin
+//! ```
+
+use clap::{Parser, ValueEnum};
+use live_subtitles_lib::audio_file::{AudioFileSource, PlaybackPace};
+use live_subtitles_lib::audio_source::{AudioSource, ChannelSource};
+use live_subtitles_lib::config::{AppConfig, AsrModelConfig, AsrModelType, ScannedModelFiles};
+use live_subtitles_lib::engine::{Engine, EngineEvent};
+use live_subtitles_lib::recognizer::{self, RecognizerConfig};
+use live_subtitles_lib::transcript::{Transcript, WrapOptions};
+use live_subtitles_lib::{AppError, AudioCapture, CaptureMode, RecognitionState, SubtitleEvent};
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+
+/// 命令行参数
+#[derive(Parser)]
+#[command(name = "live-subtitles-cli", version, about = "实时字幕命令行工具")]
+struct Args {
+    /// 模型文件夹 (未指定 --model-type 时自动识别流式模型文件)
+    #[arg(long)]
+    model_dir: Option<PathBuf>,
+
+    /// 模型类型，离线模型 (whisper / sense-voice) 必须指定
+    #[arg(long, value_enum)]
+    model_type: Option<ModelTypeArg>,
+
+    /// encoder 文件 (相对路径基于模型文件夹)
+    #[arg(long)]
+    encoder: Option<String>,
+
+    /// decoder 文件
+    #[arg(long)]
+    decoder: Option<String>,
+
+    /// joiner 文件
+    #[arg(long)]
+    joiner: Option<String>,
+
+    /// 单一模型文件 (CTC / SenseVoice)
+    #[arg(long)]
+    model: Option<String>,
+
+    /// tokens 文件
+    #[arg(long, default_value = "tokens.txt")]
+    tokens: String,
+
+    /// 离线模型的语言代码，留空表示自动检测
+    #[arg(long, default_value = "")]
+    language: String,
+
+    /// Silero VAD 模型 (离线模型分段使用)
+    #[arg(long, default_value = "silero_vad.onnx")]
+    vad_model: String,
+
+    /// 模型采样率
+    #[arg(long, default_value_t = 16000)]
+    sample_rate: u32,
+
+    /// 线程数
+    #[arg(long, default_value_t = 2)]
+    threads: i32,
+
+    /// 使用应用的配置文件 (当前模型、热词与识别参数)，代替上面的模型参数
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// 音频源
+    #[arg(long, value_enum, default_value = "loopback")]
+    source: SourceArg,
+
+    /// 麦克风设备 ID (只用于 --source mic，见应用设置中的设备列表)，默认使用默认设备
+    #[arg(long)]
+    device: Option<String>,
+
+    /// 音频文件 (--source file)
+    #[arg(long)]
+    input: Option<PathBuf>,
+
+    /// 按实际时长播放音频文件 (默认尽快处理)
+    #[arg(long)]
+    real_time: bool,
+
+    /// 输出格式
+    #[arg(long, value_enum, default_value = "text")]
+    format: FormatArg,
+
+    /// 输出文件，默认输出到标准输出
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ModelTypeArg {
+    Transducer,
+    Paraformer,
+    Zipformer2Ctc,
+    NemoCtc,
+    Whisper,
+    SenseVoice,
+}
+
+/// 音频源
+#[derive(Clone, Copy, ValueEnum)]
+enum SourceArg {
+    /// 系统音频 (loopback)
+    Loopback,
+    /// 麦克风
+    Mic,
+    /// 音频文件 (WAV/FLAC/MP3/OGG)
+    File,
+    /// 标准输入，16 位小端单声道 PCM，采样率与模型相同
+    Stdin,
+}
+
+/// 输出格式
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum FormatArg {
+    /// 每句一行的纯文本 (只输出最终结果)
+    Text,
+    /// 每个字幕事件 (包括中间结果) 一行 JSON
+    Jsonl,
+    /// 识别结束后输出 SRT 字幕
+    Srt,
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Args) -> Result<(), AppError> {
+    let (config, models_dir) = load_config(&args)?;
+    let recognizer_config = RecognizerConfig::from_app_config(&config, &models_dir)?;
+
+    // Ctrl-C 结束输入，已识别的内容 (包括未结束的语句) 正常输出
+    let engine = Engine::new();
+    {
+        let engine = engine.clone();
+        ctrlc::set_handler(move || engine.finish())
+            .map_err(|e| AppError::Internal(format!("无法设置 Ctrl-C 处理: {}", e)))?;
+    }
+
+    let mut output = Output::new(args.format, args.output.as_ref())?;
+    let source = source_from_args(&args, recognizer_config.sample_rate())?;
+    let (tx, rx) = mpsc::channel();
+    engine.set_hotwords(recognizer::hotwords_for(&config));
+    engine.start(recognizer_config, source, tx)?;
+
+    // 识别结束后引擎释放发送端，循环随之结束
+    for event in rx {
+        match event {
+            EngineEvent::Subtitle(event) => output.write(&event).map_err(output_error)?,
+            EngineEvent::ModelLoading(true) => eprintln!("Loading model..."),
+            EngineEvent::State(RecognitionState::Running) => {
+                eprintln!("Recognizing, press Ctrl-C to stop")
+            }
+            EngineEvent::Error(error) => {
+                return Err(Arc::try_unwrap(error)
+                    .unwrap_or_else(|error| AppError::Recognizer(error.to_string())))
+            }
+            _ => {}
+        }
+    }
+
+    output.finish().map_err(output_error)
+}
+
+fn output_error(e: io::Error) -> AppError {
+    AppError::Internal(format!("无法写入输出: {}", e))
+}
+
+/// 根据参数构建配置，返回 (配置, 模型文件相对路径的基准目录)
+fn load_config(args: &Args) -> Result<(AppConfig, PathBuf), AppError> {
+    let models_dir = args.model_dir.clone().unwrap_or_else(|| PathBuf::from("."));
+
+    if let Some(path) = &args.config {
+        let content = std::fs::read_to_string(path).map_err(|e| AppError::io(path, e))?;
+        let (config, _) = AppConfig::from_json(&content).map_err(AppError::Config)?;
+        return Ok((config, models_dir));
+    }
+
+    let mut model = match args.model_type {
+        Some(model_type) => model_from_args(args, model_type).map_err(AppError::Config)?,
+        None => {
+            let scanned = ScannedModelFiles::scan_directory(&models_dir).ok_or_else(|| {
+                AppError::NotFound(format!("找不到模型目录: {}", models_dir.display()))
+            })?;
+            scanned.to_model_config().ok_or_else(|| {
+                AppError::NotFound(format!(
+                    "{} 中没有完整的流式模型，请用 --model-type 指定模型文件",
+                    models_dir.display()
+                ))
+            })?
+        }
+    };
+    model.sample_rate = args.sample_rate;
+    model.num_threads = args.threads;
+
+    let config = AppConfig {
+        current_model_id: model.id.clone(),
+        models: vec![model],
+        vad_model: args.vad_model.clone(),
+        ..Default::default()
+    };
+    config.validate().map_err(AppError::Config)?;
+    Ok((config, models_dir))
+}
+
+/// 使用命令行指定的模型文件
+fn model_from_args(args: &Args, model_type: ModelTypeArg) -> Result<AsrModelConfig, String> {
+    let file = |value: &Option<String>, flag: &str| {
+        value
+            .clone()
+            .ok_or_else(|| format!("此模型类型需要 --{}", flag))
+    };
+
+    let model_type = match model_type {
+        ModelTypeArg::Transducer => AsrModelType::Transducer {
+            encoder: file(&args.encoder, "encoder")?,
+            decoder: file(&args.decoder, "decoder")?,
+            joiner: file(&args.joiner, "joiner")?,
+        },
+        ModelTypeArg::Paraformer => AsrModelType::Paraformer {
+            encoder: file(&args.encoder, "encoder")?,
+            decoder: file(&args.decoder, "decoder")?,
+        },
+        ModelTypeArg::Zipformer2Ctc => AsrModelType::Zipformer2Ctc {
+            model: file(&args.model, "model")?,
+            hlg_graph: None,
+        },
+        ModelTypeArg::NemoCtc => AsrModelType::NemoCtc {
+            model: file(&args.model, "model")?,
+            hlg_graph: None,
+        },
+        ModelTypeArg::Whisper => AsrModelType::Whisper {
+            encoder: file(&args.encoder, "encoder")?,
+            decoder: file(&args.decoder, "decoder")?,
+            language: args.language.clone(),
+        },
+        ModelTypeArg::SenseVoice => AsrModelType::SenseVoice {
+            model: file(&args.model, "model")?,
+            language: args.language.clone(),
+        },
+    };
+
+    Ok(AsrModelConfig {
+        id: "cli".to_string(),
+        name: "cli".to_string(),
+        model_dir: String::new(),
+        model_type,
+        tokens: args.tokens.clone(),
+        languages: Vec::new(),
+        sample_rate: args.sample_rate,
+        num_threads: args.threads,
+        options: Default::default(),
+    })
+}
+
+/// 根据参数选择音频源
+fn source_from_args(args: &Args, sample_rate: u32) -> Result<Box<dyn AudioSource>, AppError> {
+    // 其他音频源不使用设备 ID，不能悄悄忽略
+    if args.device.is_some() && !matches!(args.source, SourceArg::Mic) {
+        return Err(AppError::Config("--device 只用于 --source mic".to_string()));
+    }
+
+    match args.source {
+        SourceArg::Loopback => Ok(Box::new(AudioCapture::new(CaptureMode::SystemAudio, None))),
+        SourceArg::Mic => Ok(Box::new(AudioCapture::new(
+            CaptureMode::Microphone,
+            args.device.clone(),
+        ))),
+        SourceArg::File => {
+            let path = args
+                .input
+                .clone()
+                .ok_or_else(|| AppError::Config("--source file 需要 --input".to_string()))?;
+            let pace = if args.real_time {
+                PlaybackPace::RealTime
+            } else {
+                PlaybackPace::AsFastAsPossible
+            };
+            Ok(Box::new(AudioFileSource::new(path, pace)))
+        }
+        SourceArg::Stdin => Ok(Box::new(ChannelSource::new(read_stdin_pcm(sample_rate)))),
+    }
+}
+
+/// 在后台线程读取标准输入的 16 位小端 PCM，每次约 100ms
+fn read_stdin_pcm(sample_rate: u32) -> Receiver<Vec<f32>> {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut stdin = io::stdin().lock();
+        let mut buffer = vec![0u8; (sample_rate as usize / 10).max(1) * 2];
+        let mut pending = Vec::new();
+
+        loop {
+            let n = match stdin.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    eprintln!("Failed to read stdin: {}", e);
+                    break;
+                }
+            };
+
+            // 读取可能在两个字节中间结束，剩余的字节留到下次
+            pending.extend_from_slice(&buffer[..n]);
+            let usable = pending.len() / 2 * 2;
+            let samples: Vec<f32> = pending[..usable]
+                .chunks_exact(2)
+                .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+                .collect();
+            pending.drain(..usable);
+
+            if tx.send(samples).is_err() {
+                break;
+            }
+        }
+    });
+
+    rx
+}
+
+/// 字幕输出
+struct Output {
+    format: FormatArg,
+    writer: Box<dyn Write>,
+    transcript: Transcript,
+}
+
+impl Output {
+    fn new(format: FormatArg, path: Option<&PathBuf>) -> Result<Self, AppError> {
+        let writer: Box<dyn Write> = match path {
+            Some(path) => Box::new(BufWriter::new(
+                File::create(path).map_err(|e| AppError::io(path, e))?,
+            )),
+            None => Box::new(io::stdout()),
+        };
+
+        Ok(Self {
+            format,
+            writer,
+            transcript: Transcript::default(),
+        })
+    }
+
+    /// 输出一个字幕事件 (SRT 只记录最终结果，结束时统一输出)
+    fn write(&mut self, event: &SubtitleEvent) -> io::Result<()> {
+        match self.format {
+            FormatArg::Text if event.is_final => writeln!(self.writer, "{}", event.text)?,
+            FormatArg::Text => return Ok(()),
+            FormatArg::Jsonl => {
+                serde_json::to_writer(&mut self.writer, event)?;
+                writeln!(self.writer)?;
+            }
+            FormatArg::Srt => {
+                if event.is_final {
+                    self.transcript
+                        .push(event.start_time, event.end_time, &event.text);
+                }
+                return Ok(());
+            }
+        }
+        self.writer.flush()
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        if self.format == FormatArg::Srt {
+            let srt = self.transcript.to_srt(&WrapOptions::default());
+            self.writer.write_all(srt.as_bytes())?;
+        }
+        self.writer.flush()
+    }
+}
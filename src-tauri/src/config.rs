@@ -1,8 +1,129 @@
 //! 模型配置模块
 //! 支持多种 ASR 模型的灵活切换
 
+use crate::error::AppError;
+use crate::model_manifest::ModelManifest;
+use crate::model_validation;
+use crate::onnx::OnnxModelInfo;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// 配置文件名
+pub const CONFIG_FILE_NAME: &str = "config.json";
+
+/// 当前配置版本
+///
+/// 修改 `AppConfig` 的序列化格式时递增此版本，并在 `MIGRATIONS` 末尾添加对应的迁移函数，
+/// 同时在 `tests/fixtures/config/` 下添加新版本的配置样例
+pub const CONFIG_VERSION: u32 = 2;
+
+/// 配置迁移函数，`MIGRATIONS[n]` 将版本 n 的配置升级到版本 n + 1
+type Migration = fn(&mut serde_json::Map<String, serde_json::Value>);
+
+const MIGRATIONS: &[Migration] = &[migrate_v0_to_v1, migrate_v1_to_v2];
+
+/// v0 -> v1: 引入 `version` 字段，合并前端遗留的 `audioDeviceId` 字段
+fn migrate_v0_to_v1(config: &mut serde_json::Map<String, serde_json::Value>) {
+    use serde_json::Value;
+
+    // 旧版前端直接写入 camelCase 的 audioDeviceId（已废弃，保留用于兼容）
+    if let Some(legacy) = config.remove("audioDeviceId") {
+        let current_empty = config
+            .get("audio_device_id")
+            .and_then(Value::as_str)
+            .map(str::is_empty)
+            .unwrap_or(true);
+        if current_empty && legacy.is_string() {
+            config.insert("audio_device_id".to_string(), legacy);
+        }
+    }
+
+    // 旧版本可能写入 "SystemAudio" / "Microphone" 等大小写形式
+    if let Some(Value::String(source_type)) = config.get_mut("audio_source_type") {
+        *source_type = source_type.to_lowercase();
+    }
+}
+
+/// v1 -> v2: Paraformer 从单一 `model` 文件改为流式的 `encoder`/`decoder`
+///
+/// 旧的 `model` 作为 encoder 保留，decoder 取同一位置对应的 decoder 文件 (如 `encoder.int8.onnx` ->
+/// `decoder.int8.onnx`)。文件名中没有 encoder 时 decoder 留空；decoder 文件不存在时
+/// 模型在 `missing_files` 中显示为缺少 decoder，由用户重新选择
+fn migrate_v1_to_v2(config: &mut serde_json::Map<String, serde_json::Value>) {
+    use serde_json::Value;
+
+    let Some(Value::Array(models)) = config.get_mut("models") else {
+        return;
+    };
+
+    for model_type in models
+        .iter_mut()
+        .filter_map(|model| model.get_mut("model_type"))
+        .filter_map(Value::as_object_mut)
+    {
+        if model_type.get("type").and_then(Value::as_str) != Some("Paraformer") {
+            continue;
+        }
+        if let Some(model) = model_type.remove("model") {
+            let decoder = model.as_str().map(decoder_for_encoder).unwrap_or_default();
+            model_type.entry("encoder").or_insert(model);
+            model_type
+                .entry("decoder")
+                .or_insert_with(|| Value::String(decoder));
+        }
+    }
+}
+
+/// 与 encoder 文件对应的 decoder 文件 (替换文件名中最后一个 encoder)，文件名中没有 encoder 时为空
+fn decoder_for_encoder(encoder: &str) -> String {
+    let name_start = encoder.rfind(['/', '\\']).map_or(0, |i| i + 1);
+    match encoder[name_start..].rfind("encoder") {
+        Some(i) => {
+            let i = name_start + i;
+            format!(
+                "{}decoder{}",
+                &encoder[..i],
+                &encoder[i + "encoder".len()..]
+            )
+        }
+        None => String::new(),
+    }
+}
+
+/// 将任意旧版本的配置逐步迁移到当前版本
+///
+/// 返回迁移前的版本号；没有 `version` 字段的配置视为版本 0
+pub fn migrate_config(value: &mut serde_json::Value) -> Result<u32, String> {
+    let config = value
+        .as_object_mut()
+        .ok_or_else(|| "配置文件格式错误: 顶层不是对象".to_string())?;
+
+    let from_version = config
+        .get("version")
+        .and_then(serde_json::Value::as_u64)
+        .unwrap_or(0) as u32;
+
+    if from_version > CONFIG_VERSION {
+        // 新版本应用写入的配置，尽量按当前格式读取
+        eprintln!(
+            "[Config] Config version {} is newer than supported version {}",
+            from_version, CONFIG_VERSION
+        );
+        return Ok(from_version);
+    }
+
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(from_version as usize) {
+        eprintln!(
+            "[Config] Migrating config from v{} to v{}",
+            version,
+            version + 1
+        );
+        migration(config);
+    }
+
+    config.insert("version".to_string(), CONFIG_VERSION.into());
+    Ok(from_version)
+}
 
 /// ASR 模型类型
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -14,12 +135,105 @@ pub enum AsrModelType {
         decoder: String,
         joiner: String,
     },
-    /// Paraformer 模型 (单一模型文件)
-    Paraformer { model: String },
-    /// Whisper 模型
-    Whisper { encoder: String, decoder: String },
-    /// SenseVoice 模型
-    SenseVoice { model: String },
+    /// 流式 Paraformer 模型 (encoder/decoder)
+    Paraformer { encoder: String, decoder: String },
+    /// 流式 Zipformer2 CTC 模型 (单一模型文件，可选 HLG 解码图)
+    Zipformer2Ctc {
+        model: String,
+        #[serde(default)]
+        hlg_graph: Option<String>,
+    },
+    /// 流式 NeMo CTC 模型 (单一模型文件，可选 HLG 解码图)
+    NemoCtc {
+        model: String,
+        #[serde(default)]
+        hlg_graph: Option<String>,
+    },
+    /// Whisper 模型 (离线，配合 VAD 分段识别)
+    Whisper {
+        encoder: String,
+        decoder: String,
+        /// 语言代码，空字符串表示自动检测
+        #[serde(default)]
+        language: String,
+    },
+    /// SenseVoice 模型 (离线，配合 VAD 分段识别)
+    SenseVoice {
+        model: String,
+        /// 语言代码，空字符串表示自动检测
+        #[serde(default)]
+        language: String,
+    },
+}
+
+impl AsrModelType {
+    /// 是否是离线 (非流式) 模型
+    pub fn is_offline(&self) -> bool {
+        matches!(
+            self,
+            AsrModelType::Whisper { .. } | AsrModelType::SenseVoice { .. }
+        )
+    }
+
+    /// 对应的模型种类
+    pub fn kind(&self) -> ModelKind {
+        match self {
+            AsrModelType::Transducer { .. } => ModelKind::Transducer,
+            AsrModelType::Paraformer { .. } => ModelKind::Paraformer,
+            AsrModelType::Zipformer2Ctc { .. } => ModelKind::Zipformer2Ctc,
+            AsrModelType::NemoCtc { .. } => ModelKind::NemoCtc,
+            AsrModelType::Whisper { .. } => ModelKind::Whisper,
+            AsrModelType::SenseVoice { .. } => ModelKind::SenseVoice,
+        }
+    }
+
+    /// 类型名称 (与序列化的 `type` 字段相同)
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            AsrModelType::Transducer { .. } => "Transducer",
+            AsrModelType::Paraformer { .. } => "Paraformer",
+            AsrModelType::Zipformer2Ctc { .. } => "Zipformer2Ctc",
+            AsrModelType::NemoCtc { .. } => "NemoCtc",
+            AsrModelType::Whisper { .. } => "Whisper",
+            AsrModelType::SenseVoice { .. } => "SenseVoice",
+        }
+    }
+
+    /// 模型文件及其用途 (encoder/decoder/joiner/model/hlg_graph)，不含 tokens
+    pub fn files(&self) -> Vec<(&'static str, &str)> {
+        match self {
+            AsrModelType::Transducer {
+                encoder,
+                decoder,
+                joiner,
+            } => vec![
+                ("encoder", encoder),
+                ("decoder", decoder),
+                ("joiner", joiner),
+            ],
+            AsrModelType::Paraformer { encoder, decoder }
+            | AsrModelType::Whisper {
+                encoder, decoder, ..
+            } => vec![("encoder", encoder), ("decoder", decoder)],
+            AsrModelType::Zipformer2Ctc { model, .. } | AsrModelType::NemoCtc { model, .. } => {
+                let mut files = vec![("model", model.as_str())];
+                files.extend(self.hlg_graph().map(|graph| ("hlg_graph", graph)));
+                files
+            }
+            AsrModelType::SenseVoice { model, .. } => vec![("model", model)],
+        }
+    }
+
+    /// CTC 模型的 HLG 解码图 (其他模型类型为 None)
+    pub fn hlg_graph(&self) -> Option<&str> {
+        match self {
+            AsrModelType::Zipformer2Ctc { hlg_graph, .. }
+            | AsrModelType::NemoCtc { hlg_graph, .. } => {
+                hlg_graph.as_deref().filter(|g| !g.is_empty())
+            }
+            _ => None,
+        }
+    }
 }
 
 /// ASR 模型配置
@@ -43,9 +257,59 @@ pub struct AsrModelConfig {
     /// 线程数
     #[serde(default = "default_num_threads")]
     pub num_threads: i32,
+    /// endpoint 规则与解码选项
+    #[serde(default)]
+    pub options: RecognizerOptions,
 }
 
-fn default_sample_rate() -> u32 {
+impl AsrModelConfig {
+    /// 检查配置是否有效
+    pub fn validate(&self) -> Result<(), String> {
+        if self.sample_rate == 0 {
+            return Err(format!("模型 {}: 采样率必须大于 0", self.name));
+        }
+        if self.num_threads < 1 {
+            return Err(format!("模型 {}: 线程数必须大于 0", self.name));
+        }
+
+        self.options
+            .validate()
+            .map_err(|e| format!("模型 {}: {}", self.name, e))?;
+
+        // 只有 Transducer 模型支持 modified_beam_search
+        if self.options.decoding_method == DecodingMethod::ModifiedBeamSearch
+            && !matches!(self.model_type, AsrModelType::Transducer { .. })
+        {
+            return Err(format!(
+                "模型 {}: modified_beam_search 只支持 Transducer 模型",
+                self.name
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// 识别器需要的全部文件及其用途 (模型文件、tokens，以及配置了的 bpe_vocab)
+    pub fn files(&self) -> Vec<(&'static str, &str)> {
+        let mut files = self.model_type.files();
+        files.push(("tokens", &self.tokens));
+        if !self.options.bpe_vocab.is_empty() {
+            files.push(("bpe_vocab", &self.options.bpe_vocab));
+        }
+        files
+    }
+
+    /// 不存在的模型文件的用途 (相对路径基于模型目录)
+    pub fn missing_files(&self, models_dir: &Path) -> Vec<String> {
+        self.files()
+            .into_iter()
+            .filter(|(_, file)| file.is_empty() || !models_dir.join(file).is_file())
+            .map(|(role, _)| role.to_string())
+            .collect()
+    }
+}
+
+pub(crate) fn default_sample_rate() -> u32 {
     16000
 }
 
@@ -53,6 +317,115 @@ fn default_num_threads() -> i32 {
     2
 }
 
+/// 解码方法
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DecodingMethod {
+    /// 贪心搜索
+    #[default]
+    GreedySearch,
+    /// 改进的束搜索 (仅 Transducer 模型)
+    ModifiedBeamSearch,
+}
+
+impl DecodingMethod {
+    /// sherpa-onnx 使用的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DecodingMethod::GreedySearch => "greedy_search",
+            DecodingMethod::ModifiedBeamSearch => "modified_beam_search",
+        }
+    }
+}
+
+/// 识别器选项 (endpoint 规则与解码选项)，缺省字段使用默认值
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RecognizerOptions {
+    /// 特征维度
+    pub feature_dim: i32,
+    /// 是否启用 endpoint 检测
+    pub enable_endpoint: bool,
+    /// Rule1: 尾部静音最小时长 (秒) - 用于检测句子结束
+    pub rule1_min_trailing_silence: f32,
+    /// Rule2: 尾部静音最小时长 (秒) - 用于中间停顿
+    pub rule2_min_trailing_silence: f32,
+    /// Rule3: 最小语句长度 (秒)
+    pub rule3_min_utterance_length: f32,
+    /// 解码方法
+    pub decoding_method: DecodingMethod,
+    /// modified_beam_search 保留的最大路径数
+    pub max_active_paths: i32,
+    /// 建模单元 (cjkchar / bpe / cjkchar+bpe)，热词编码时使用
+    pub modeling_unit: String,
+    /// bpe.vocab 文件路径 (bpe 建模单元的热词需要，相对路径基于模型目录)
+    pub bpe_vocab: String,
+}
+
+impl Default for RecognizerOptions {
+    fn default() -> Self {
+        Self {
+            feature_dim: 80,
+            enable_endpoint: true,
+            rule1_min_trailing_silence: 2.4,
+            rule2_min_trailing_silence: 1.2,
+            rule3_min_utterance_length: 20.0,
+            decoding_method: DecodingMethod::GreedySearch,
+            max_active_paths: 4,
+            modeling_unit: "cjkchar".to_string(),
+            bpe_vocab: String::new(),
+        }
+    }
+}
+
+impl RecognizerOptions {
+    /// 检查选项取值范围
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=512).contains(&self.feature_dim) {
+            return Err(format!("特征维度无效: {}", self.feature_dim));
+        }
+
+        let durations = [
+            (
+                "rule1_min_trailing_silence",
+                self.rule1_min_trailing_silence,
+            ),
+            (
+                "rule2_min_trailing_silence",
+                self.rule2_min_trailing_silence,
+            ),
+            (
+                "rule3_min_utterance_length",
+                self.rule3_min_utterance_length,
+            ),
+        ];
+        for (name, value) in durations {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(format!("{} 必须是大于 0 的秒数: {}", name, value));
+            }
+        }
+
+        if !["cjkchar", "bpe", "cjkchar+bpe"].contains(&self.modeling_unit.as_str()) {
+            return Err(format!("不支持的建模单元: {}", self.modeling_unit));
+        }
+        if self.modeling_unit.contains("bpe") && self.bpe_vocab.is_empty() {
+            return Err(format!(
+                "建模单元 {} 需要 bpe_vocab 文件",
+                self.modeling_unit
+            ));
+        }
+
+        if self.max_active_paths < 1 {
+            return Err(format!(
+                "max_active_paths 必须大于 0: {}",
+                self.max_active_paths
+            ));
+        }
+
+        Ok(())
+    }
+}
+
 /// 模型版本信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelVariant {
@@ -62,8 +435,47 @@ pub struct ModelVariant {
     pub encoder: String,
     /// decoder 文件路径
     pub decoder: String,
-    /// joiner 文件路径
+    /// joiner 文件路径（Paraformer 模型为空）
     pub joiner: String,
+    /// 单一模型文件路径（CTC/SenseVoice 模型，其他模型为空）
+    #[serde(default)]
+    pub model: String,
+    /// model.json 声明的该版本的模型类型（包括 HLG 解码图和语言），按文件名识别时为 None
+    #[serde(default)]
+    pub model_type: Option<AsrModelType>,
+}
+
+/// 扫描识别出的模型种类
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelKind {
+    /// encoder/decoder/joiner
+    #[default]
+    Transducer,
+    /// 流式 Paraformer（encoder/decoder，没有 joiner）
+    Paraformer,
+    /// 单一模型文件的 Zipformer2 CTC
+    Zipformer2Ctc,
+    /// 单一模型文件的 NeMo CTC
+    NemoCtc,
+    /// Whisper（model.json 声明或按 encoder 的元数据识别）
+    Whisper,
+    /// SenseVoice（model.json 声明或按元数据识别）
+    SenseVoice,
+}
+
+impl ModelKind {
+    /// 对应的模型类型名称 (与 `AsrModelType::type_name` 相同)
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            ModelKind::Transducer => "Transducer",
+            ModelKind::Paraformer => "Paraformer",
+            ModelKind::Zipformer2Ctc => "Zipformer2Ctc",
+            ModelKind::NemoCtc => "NemoCtc",
+            ModelKind::Whisper => "Whisper",
+            ModelKind::SenseVoice => "SenseVoice",
+        }
+    }
 }
 
 /// 扫描模型文件夹的结果
@@ -75,12 +487,18 @@ pub struct ScannedModelFiles {
     pub model_name: String,
     /// 模型文件夹完整路径
     pub model_dir: String,
+    /// 模型种类
+    pub model_kind: ModelKind,
     /// 检测到的 encoder 文件（默认版本）
     pub encoder: Option<String>,
     /// 检测到的 decoder 文件（默认版本）
     pub decoder: Option<String>,
     /// 检测到的 joiner 文件（默认版本）
     pub joiner: Option<String>,
+    /// 检测到的单一模型文件（CTC 模型）
+    pub model: Option<String>,
+    /// 检测到的 HLG 解码图（CTC 模型可选）
+    pub hlg_graph: Option<String>,
     /// 检测到的 tokens 文件
     pub tokens: Option<String>,
     /// 是否是完整的模型（包含所有必需文件）
@@ -89,15 +507,41 @@ pub struct ScannedModelFiles {
     pub variants: Vec<ModelVariant>,
     /// 是否有多个版本
     pub has_multiple_variants: bool,
+    /// model.json 声明的模型类型（默认版本，文件为绝对路径），按文件名识别时为 None
+    #[serde(default)]
+    pub model_type: Option<AsrModelType>,
+    /// model.json 声明的语言
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// model.json 声明的采样率
+    #[serde(default)]
+    pub sample_rate: Option<u32>,
+    /// model.json 推荐的识别参数
+    #[serde(default)]
+    pub options: Option<RecognizerOptions>,
+    /// model.json 无法解析时的错误（此时使用按文件名识别的结果）
+    #[serde(default)]
+    pub manifest_error: Option<String>,
 }
 
 impl ScannedModelFiles {
     /// 扫描指定文件夹，自动识别模型文件
+    ///
+    /// 文件夹中有 model.json 时以其为准，否则按文件名识别
     pub fn scan_directory(dir: &PathBuf) -> Option<Self> {
         if !dir.is_dir() {
             return None;
         }
 
+        let manifest_error = match ModelManifest::read(dir) {
+            Ok(Some(manifest)) => return Some(manifest.to_scanned(dir)),
+            Ok(None) => None,
+            Err(e) => {
+                eprintln!("[Scan] Invalid manifest in {}: {}", dir.display(), e);
+                Some(e)
+            }
+        };
+
         let model_name = dir
             .file_name()
             .and_then(|n| n.to_str())
@@ -110,19 +554,28 @@ impl ScannedModelFiles {
             id: model_name.clone(),
             model_name,
             model_dir,
+            model_kind: ModelKind::Transducer,
             encoder: None,
             decoder: None,
             joiner: None,
+            model: None,
+            hlg_graph: None,
             tokens: None,
             is_complete: false,
             variants: Vec::new(),
             has_multiple_variants: false,
+            model_type: None,
+            languages: Vec::new(),
+            sample_rate: None,
+            options: None,
+            manifest_error,
         };
 
         // 收集所有文件
         let mut encoders = Vec::new();
         let mut decoders = Vec::new();
         let mut joiners = Vec::new();
+        let mut single_models = Vec::new();
 
         // 遍历目录中的文件
         if let Ok(entries) = std::fs::read_dir(dir) {
@@ -139,7 +592,7 @@ impl ScannedModelFiles {
                     .to_lowercase();
 
                 // 调试日志
-                println!(
+                eprintln!(
                     "[Scan] Found file: {} (lowercase: {})",
                     path.display(),
                     file_name
@@ -147,22 +600,31 @@ impl ScannedModelFiles {
 
                 // 收集所有模型文件
                 if file_name.contains("encoder") && file_name.ends_with(".onnx") {
-                    println!("[Scan]   -> Matched as encoder");
+                    eprintln!("[Scan]   -> Matched as encoder");
                     encoders.push(path.to_string_lossy().to_string());
                 } else if file_name.contains("decoder") && file_name.ends_with(".onnx") {
-                    println!("[Scan]   -> Matched as decoder");
+                    eprintln!("[Scan]   -> Matched as decoder");
                     decoders.push(path.to_string_lossy().to_string());
                 } else if file_name.contains("joiner") && file_name.ends_with(".onnx") {
-                    println!("[Scan]   -> Matched as joiner");
+                    eprintln!("[Scan]   -> Matched as joiner");
                     joiners.push(path.to_string_lossy().to_string());
                 } else if file_name.contains("tokens") && file_name.ends_with(".txt") {
-                    println!("[Scan]   -> Matched as tokens");
+                    eprintln!("[Scan]   -> Matched as tokens");
                     result.tokens = Some(path.to_string_lossy().to_string());
+                } else if file_name == "hlg.fst" {
+                    eprintln!("[Scan]   -> Matched as HLG graph");
+                    result.hlg_graph = Some(path.to_string_lossy().to_string());
+                } else if file_name.ends_with(".onnx") && file_name.contains("vad") {
+                    // 与模型放在一起的 VAD 模型 (如 silero_vad.onnx) 不是识别模型
+                    eprintln!("[Scan]   -> Skipped VAD model");
+                } else if file_name.ends_with(".onnx") {
+                    eprintln!("[Scan]   -> Matched as single-file model");
+                    single_models.push(path.to_string_lossy().to_string());
                 }
             }
         }
 
-        println!(
+        eprintln!(
             "[Scan] Result for {}: encoder={:?}, decoder={:?}, joiner={:?}, tokens={:?}",
             result.model_name,
             result.encoder.is_some(),
@@ -171,6 +633,43 @@ impl ScannedModelFiles {
             result.tokens.is_some()
         );
 
+        // 有 encoder/decoder 但没有 joiner 的可能是流式 Paraformer 或 Whisper，按 encoder 的元数据区分
+        if joiners.is_empty() && !encoders.is_empty() && !decoders.is_empty() {
+            result.model_kind = encoders
+                .iter()
+                .find_map(|encoder| encoder_decoder_kind(encoder))
+                .unwrap_or_else(|| {
+                    // 没有元数据时按名称识别
+                    if result.model_name.to_lowercase().contains("whisper") {
+                        ModelKind::Whisper
+                    } else {
+                        ModelKind::Paraformer
+                    }
+                });
+        } else if encoders.is_empty() && !single_models.is_empty() {
+            // 只有单一模型文件的按 ONNX 元数据识别 (流式 CTC 或 SenseVoice)。
+            // 无法识别的文件不使用，模型显示为缺少 model 文件
+            let recognized: Vec<(&String, ModelKind)> = single_models
+                .iter()
+                .filter_map(|model| Some((model, single_file_kind(model)?)))
+                .collect();
+            result.model_kind = recognized
+                .first()
+                .map_or(ModelKind::Zipformer2Ctc, |(_, kind)| *kind);
+
+            let models: Vec<&String> = recognized
+                .iter()
+                .filter(|(_, kind)| *kind == result.model_kind)
+                .map(|(model, _)| *model)
+                .collect();
+            result.model = models
+                .iter()
+                .find(|m| m.contains("int8"))
+                .or_else(|| models.first())
+                .map(|m| m.to_string());
+        }
+        let needs_joiner = result.model_kind == ModelKind::Transducer;
+
         // 优先选择 int8 版本作为默认版本（更快）
         result.encoder = encoders
             .iter()
@@ -189,14 +688,18 @@ impl ScannedModelFiles {
             .cloned();
 
         // 检测多版本（如果 encoder/decoder/joiner 都有多个文件）
-        if encoders.len() > 1 && decoders.len() > 1 && joiners.len() > 1 {
+        if encoders.len() > 1 && decoders.len() > 1 && (!needs_joiner || joiners.len() > 1) {
             result.has_multiple_variants = true;
 
             // 尝试匹配版本对（int8 和默认版本）
             // 1. 先找 int8 版本
             let int8_encoder = encoders.iter().find(|e| e.contains("int8"));
             let int8_decoder = decoders.iter().find(|d| d.contains("int8"));
-            let int8_joiner = joiners.iter().find(|j| j.contains("int8"));
+            let int8_joiner = if needs_joiner {
+                joiners.iter().find(|j| j.contains("int8")).cloned()
+            } else {
+                Some(String::new())
+            };
 
             if let (Some(encoder), Some(decoder), Some(joiner)) =
                 (int8_encoder, int8_decoder, int8_joiner)
@@ -205,14 +708,20 @@ impl ScannedModelFiles {
                     variant_name: "int8".to_string(),
                     encoder: encoder.clone(),
                     decoder: decoder.clone(),
-                    joiner: joiner.clone(),
+                    joiner,
+                    model: String::new(),
+                    model_type: None,
                 });
             }
 
             // 2. 再找默认版本（不含 int8 的就是 fp32）
             let fp32_encoder = encoders.iter().find(|e| !e.contains("int8"));
             let fp32_decoder = decoders.iter().find(|d| !d.contains("int8"));
-            let fp32_joiner = joiners.iter().find(|j| !j.contains("int8"));
+            let fp32_joiner = if needs_joiner {
+                joiners.iter().find(|j| !j.contains("int8")).cloned()
+            } else {
+                Some(String::new())
+            };
 
             if let (Some(encoder), Some(decoder), Some(joiner)) =
                 (fp32_encoder, fp32_decoder, fp32_joiner)
@@ -221,40 +730,313 @@ impl ScannedModelFiles {
                     variant_name: "fp32".to_string(),
                     encoder: encoder.clone(),
                     decoder: decoder.clone(),
-                    joiner: joiner.clone(),
+                    joiner,
+                    model: String::new(),
+                    model_type: None,
                 });
             }
         }
 
         // 计算是否完整
-        result.is_complete = result.encoder.is_some()
-            && result.decoder.is_some()
-            && result.joiner.is_some()
-            && result.tokens.is_some();
+        let has_model_files = match result.model_kind {
+            ModelKind::Transducer => {
+                result.encoder.is_some() && result.decoder.is_some() && result.joiner.is_some()
+            }
+            ModelKind::Paraformer | ModelKind::Whisper => {
+                result.encoder.is_some() && result.decoder.is_some()
+            }
+            ModelKind::Zipformer2Ctc | ModelKind::NemoCtc | ModelKind::SenseVoice => {
+                result.model.is_some()
+            }
+        };
+        result.is_complete = has_model_files && result.tokens.is_some();
 
         Some(result)
     }
+
+    /// 没有找到的必需文件的用途
+    pub fn missing_files(&self) -> Vec<String> {
+        let required = match self.model_kind {
+            ModelKind::Transducer => vec![
+                ("encoder", &self.encoder),
+                ("decoder", &self.decoder),
+                ("joiner", &self.joiner),
+            ],
+            ModelKind::Paraformer | ModelKind::Whisper => {
+                vec![("encoder", &self.encoder), ("decoder", &self.decoder)]
+            }
+            ModelKind::Zipformer2Ctc | ModelKind::NemoCtc | ModelKind::SenseVoice => {
+                vec![("model", &self.model)]
+            }
+        };
+        required
+            .into_iter()
+            .chain([("tokens", &self.tokens)])
+            .filter(|(_, file)| file.is_none())
+            .map(|(role, _)| role.to_string())
+            .collect()
+    }
+
+    /// 转换为模型配置 (文件使用绝对路径)，模型不完整时返回 None
+    pub fn to_model_config(&self) -> Option<AsrModelConfig> {
+        if !self.is_complete {
+            return None;
+        }
+
+        let model_type = match (&self.model_type, self.model_kind) {
+            (Some(model_type), _) => model_type.clone(),
+            (None, kind) => self.guessed_model_type(kind)?,
+        };
+
+        Some(AsrModelConfig {
+            id: self.id.clone(),
+            name: self.model_name.clone(),
+            model_dir: self.model_dir.clone(),
+            model_type,
+            tokens: self.tokens.clone()?,
+            languages: self.languages.clone(),
+            sample_rate: self.sample_rate.unwrap_or_else(default_sample_rate),
+            num_threads: default_num_threads(),
+            options: self.options.clone().unwrap_or_default(),
+        })
+    }
+
+    /// 按文件名识别出的文件组成模型类型
+    fn guessed_model_type(&self, kind: ModelKind) -> Option<AsrModelType> {
+        let model_type = match kind {
+            ModelKind::Transducer => AsrModelType::Transducer {
+                encoder: self.encoder.clone()?,
+                decoder: self.decoder.clone()?,
+                joiner: self.joiner.clone()?,
+            },
+            ModelKind::Paraformer => AsrModelType::Paraformer {
+                encoder: self.encoder.clone()?,
+                decoder: self.decoder.clone()?,
+            },
+            ModelKind::Zipformer2Ctc => AsrModelType::Zipformer2Ctc {
+                model: self.model.clone()?,
+                hlg_graph: self.hlg_graph.clone(),
+            },
+            ModelKind::NemoCtc => AsrModelType::NemoCtc {
+                model: self.model.clone()?,
+                hlg_graph: self.hlg_graph.clone(),
+            },
+            ModelKind::Whisper => AsrModelType::Whisper {
+                encoder: self.encoder.clone()?,
+                decoder: self.decoder.clone()?,
+                language: String::new(),
+            },
+            ModelKind::SenseVoice => AsrModelType::SenseVoice {
+                model: self.model.clone()?,
+                language: String::new(),
+            },
+        };
+        Some(model_type)
+    }
 }
 
-/// 音频源类型
+/// 根据 ONNX 元数据判断单一模型文件的种类
+///
+/// 支持 SenseVoice 和流式的 NeMo CTC (有 `cache_last_channel_dim1`) / Zipformer2 CTC；
+/// 离线 CTC、无法读取或没有元数据的文件返回 None
+fn single_file_kind(model: &str) -> Option<ModelKind> {
+    let info = OnnxModelInfo::read(Path::new(model)).ok()?;
+    let model_type = info.model_type().unwrap_or_default();
+
+    if model_type.contains("sense_voice") || model_type.contains("sensevoice") {
+        return Some(ModelKind::SenseVoice);
+    }
+    if model_validation::is_streaming(&info) != Some(true) {
+        return None;
+    }
+    if info.metadata.contains_key("cache_last_channel_dim1")
+        || model_type.contains("nemo")
+        || model_type.contains("encdec")
+    {
+        Some(ModelKind::NemoCtc)
+    } else if model_type.contains("zipformer") {
+        Some(ModelKind::Zipformer2Ctc)
+    } else {
+        None
+    }
+}
+
+/// 根据 encoder 的 ONNX 元数据判断 encoder/decoder 模型 (没有 joiner) 的种类
+///
+/// 无法读取元数据或元数据中没有模型信息时返回 None；
+/// 元数据表明是其他模型 (如 Zipformer transducer) 时视为缺少 joiner 的 Transducer
+fn encoder_decoder_kind(encoder: &str) -> Option<ModelKind> {
+    let info = OnnxModelInfo::read(Path::new(encoder)).ok()?;
+    let model_type = info.model_type();
+
+    if model_type.as_deref().is_some_and(|t| t.contains("whisper")) {
+        Some(ModelKind::Whisper)
+    } else if model_type
+        .as_deref()
+        .is_some_and(|t| t.contains("paraformer"))
+        || info.metadata.contains_key("lfr_window_size")
+    {
+        Some(ModelKind::Paraformer)
+    } else if model_type.is_some() {
+        Some(ModelKind::Transducer)
+    } else {
+        None
+    }
+}
+
+/// 热词 (上下文偏置)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Hotword {
+    /// 热词短语
+    pub phrase: String,
+    /// 该词的加分，None 表示使用全局的 `hotwords_score`
+    #[serde(default)]
+    pub boost: Option<f32>,
+}
+
+impl Hotword {
+    /// 检查热词是否有效
+    pub fn validate(&self) -> Result<(), String> {
+        let phrase = self.phrase.trim();
+        if phrase.is_empty() {
+            return Err("热词不能为空".to_string());
+        }
+        // 热词文件格式为每行一个热词，加分以 `:` 开头
+        if phrase.contains(['\n', '\r', ':']) {
+            return Err(format!("热词不能包含换行或冒号: {}", phrase));
+        }
+        if let Some(boost) = self.boost {
+            if !boost.is_finite() || boost <= 0.0 {
+                return Err(format!("热词 {} 的加分必须大于 0: {}", phrase, boost));
+            }
+        }
+        Ok(())
+    }
+
+    /// 转换为 sherpa-onnx 热词文件中的一行，例如 `语音识别 :2.0`
+    ///
+    /// 没有单独设置加分时使用 `default_boost`
+    pub fn to_line(&self, default_boost: f32) -> String {
+        format!(
+            "{} :{}",
+            self.phrase.trim(),
+            self.boost.unwrap_or(default_boost)
+        )
+    }
+}
+
+/// 生成 sherpa-onnx 热词内容 (每行一个热词，跳过无效的热词)
+///
+/// 每个热词都写明加分，修改默认加分后无需重新加载识别器
+pub fn hotwords_buffer(hotwords: &[Hotword], default_boost: f32) -> String {
+    hotwords
+        .iter()
+        .filter(|hotword| hotword.validate().is_ok())
+        .map(|hotword| hotword.to_line(default_boost))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 字幕自动保存配置 (每次识别保存为一个 JSONL 会话)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AutosaveConfig {
+    /// 是否启用自动保存
+    pub enabled: bool,
+    /// 保存目录 (空字符串表示应用数据目录下的 transcripts)
+    pub directory: String,
+    /// 单个文件的最大大小 (KB)，超过后写入新文件
+    pub max_file_size_kb: u64,
+    /// 会话保留天数，0 表示不按时间清理
+    pub retention_days: u32,
+    /// 最多保留的会话数，0 表示不限制
+    pub max_sessions: usize,
+}
+
+impl Default for AutosaveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: String::new(),
+            max_file_size_kb: 1024,
+            retention_days: 30,
+            max_sessions: 100,
+        }
+    }
+}
+
+impl AutosaveConfig {
+    /// 检查配置取值范围
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_file_size_kb == 0 {
+            return Err("自动保存的文件大小上限必须大于 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 字幕推送服务配置 (WebSocket)
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 监听地址
+    pub bind_address: String,
+    /// 监听端口
+    pub port: u16,
+    /// 访问令牌，空字符串表示不需要认证
+    pub token: String,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: "127.0.0.1".to_string(),
+            port: 9876,
+            token: String::new(),
+        }
+    }
+}
+
+impl ServerConfig {
+    /// 检查监听地址是否有效
+    pub fn validate(&self) -> Result<(), String> {
+        self.socket_addr().map(|_| ())
+    }
+
+    /// 解析监听地址
+    pub fn socket_addr(&self) -> Result<std::net::SocketAddr, String> {
+        if self.port == 0 {
+            return Err("字幕推送服务的端口不能为 0".to_string());
+        }
+        let ip: std::net::IpAddr = self
+            .bind_address
+            .trim()
+            .parse()
+            .map_err(|_| format!("无效的监听地址: {}", self.bind_address))?;
+        Ok(std::net::SocketAddr::new(ip, self.port))
+    }
+}
+
+/// 音频源类型
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum AudioSourceType {
     /// 系统音频 (loopback)
+    #[default]
     SystemAudio,
     /// 麦克风输入
     Microphone,
 }
 
-impl Default for AudioSourceType {
-    fn default() -> Self {
-        AudioSourceType::SystemAudio
-    }
-}
-
 /// 应用配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
+    /// 配置版本，用于迁移旧版配置文件
+    #[serde(default = "default_config_version")]
+    pub version: u32,
     /// 当前使用的模型 ID
     pub current_model_id: String,
     /// 可用的 ASR 模型列表
@@ -265,11 +1047,42 @@ pub struct AppConfig {
     /// 音频设备 ID（空字符串表示使用默认设备）
     #[serde(default)]
     pub audio_device_id: String,
+    /// Silero VAD 模型路径（离线模型分段使用，相对路径基于模型目录）
+    #[serde(default = "default_vad_model")]
+    pub vad_model: String,
+    /// 热词列表 (仅 modified_beam_search 生效)
+    #[serde(default)]
+    pub hotwords: Vec<Hotword>,
+    /// 热词的默认加分
+    #[serde(default = "default_hotwords_score")]
+    pub hotwords_score: f32,
+    /// 字幕自动保存
+    #[serde(default)]
+    pub autosave: AutosaveConfig,
+    /// 字幕推送服务
+    #[serde(default)]
+    pub server: ServerConfig,
+    /// 额外扫描的模型根目录 (空字符串表示只扫描应用的模型目录)
+    #[serde(default)]
+    pub models_root_dir: String,
+}
+
+fn default_hotwords_score() -> f32 {
+    1.5
+}
+
+fn default_vad_model() -> String {
+    "silero_vad.onnx".to_string()
+}
+
+fn default_config_version() -> u32 {
+    CONFIG_VERSION
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             current_model_id: "default".to_string(),
             models: vec![AsrModelConfig {
                 id: "default".to_string(),
@@ -284,9 +1097,16 @@ impl Default for AppConfig {
                 languages: vec!["zh".to_string(), "en".to_string()],
                 sample_rate: 16000,
                 num_threads: 2,
+                options: RecognizerOptions::default(),
             }],
             audio_source_type: AudioSourceType::default(),
             audio_device_id: String::new(), // 空字符串表示使用默认设备
+            vad_model: default_vad_model(),
+            hotwords: Vec::new(),
+            hotwords_score: default_hotwords_score(),
+            autosave: AutosaveConfig::default(),
+            server: ServerConfig::default(),
+            models_root_dir: String::new(),
         }
     }
 }
@@ -297,6 +1117,18 @@ impl AppConfig {
         self.models.iter().find(|m| m.id == self.current_model_id)
     }
 
+    /// 检查所有模型配置是否有效
+    pub fn validate(&self) -> Result<(), String> {
+        self.models.iter().try_for_each(AsrModelConfig::validate)?;
+
+        if !self.hotwords_score.is_finite() || self.hotwords_score <= 0.0 {
+            return Err(format!("热词加分必须大于 0: {}", self.hotwords_score));
+        }
+        self.hotwords.iter().try_for_each(Hotword::validate)?;
+        self.autosave.validate()?;
+        self.server.validate()
+    }
+
     /// 切换模型
     #[allow(dead_code)]
     pub fn switch_model(&mut self, model_id: &str) -> bool {
@@ -319,9 +1151,80 @@ impl AppConfig {
         }
     }
 
+    /// 从配置文件加载配置
+    ///
+    /// 文件不存在时返回默认配置；文件损坏时将其备份为 `*.corrupt` 并返回默认配置
+    pub fn load(path: &Path) -> Self {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    eprintln!("[Config] Failed to read {}: {}", path.display(), e);
+                }
+                return Self::default();
+            }
+        };
+
+        match Self::from_json(&content) {
+            Ok((config, from_version)) => {
+                eprintln!("[Config] Loaded config from {}", path.display());
+                // 迁移后立即写回，避免每次启动都重复迁移
+                if from_version < CONFIG_VERSION {
+                    if let Err(e) = config.save(path) {
+                        eprintln!("[Config] Failed to save migrated config: {}", e);
+                    }
+                }
+                config
+            }
+            Err(e) => {
+                eprintln!("[Config] Corrupt config file {}: {}", path.display(), e);
+                let backup = path.with_extension("json.corrupt");
+                if let Err(e) = std::fs::rename(path, &backup) {
+                    eprintln!("[Config] Failed to back up corrupt config: {}", e);
+                }
+                Self::default()
+            }
+        }
+    }
+
+    /// 从 JSON 解析配置，必要时先迁移到当前版本
+    ///
+    /// 返回 (配置, 迁移前的版本号)
+    pub fn from_json(content: &str) -> Result<(Self, u32), String> {
+        let mut value: serde_json::Value =
+            serde_json::from_str(content).map_err(|e| e.to_string())?;
+        let from_version = migrate_config(&mut value)?;
+        let config = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        Ok((config, from_version))
+    }
+
+    /// 保存配置到文件
+    ///
+    /// 先写入临时文件再重命名，避免写入过程中崩溃导致配置文件损坏
+    pub fn save(&self, path: &Path) -> Result<(), AppError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+        }
+
+        let content =
+            serde_json::to_string_pretty(self).map_err(|e| AppError::Internal(e.to_string()))?;
+        let tmp_path = path.with_extension("json.tmp");
+
+        {
+            use std::io::Write;
+
+            let mut file =
+                std::fs::File::create(&tmp_path).map_err(|e| AppError::io(&tmp_path, e))?;
+            file.write_all(content.as_bytes())
+                .and_then(|_| file.sync_all())
+                .map_err(|e| AppError::io(&tmp_path, e))?;
+        }
+
+        std::fs::rename(&tmp_path, path).map_err(|e| AppError::io(path, e))
+    }
+
     /// 获取模型的绝对路径
-    #[allow(dead_code)]
-    pub fn resolve_model_path(&self, relative_path: &str, base_dir: &PathBuf) -> PathBuf {
+    pub fn resolve_model_path(&self, relative_path: &str, base_dir: &Path) -> PathBuf {
         let path = PathBuf::from(relative_path);
         if path.is_absolute() {
             path
@@ -334,6 +1237,8 @@ impl AppConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::onnx::tests::fake_model;
+    use crate::test_util::TempDir;
 
     #[test]
     fn test_default_config() {
@@ -342,10 +1247,362 @@ mod tests {
         assert!(config.current_model().is_some());
     }
 
+    #[test]
+    fn test_recognizer_options_validation() {
+        let mut config = AppConfig::default();
+        assert!(config.validate().is_ok());
+
+        config.models[0].options.rule2_min_trailing_silence = 0.0;
+        assert!(config.validate().is_err());
+
+        config.models[0].options = RecognizerOptions {
+            decoding_method: DecodingMethod::ModifiedBeamSearch,
+            max_active_paths: 0,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        config.models[0].options.max_active_paths = 8;
+        assert!(config.validate().is_ok());
+
+        // modified_beam_search 只支持 Transducer
+        config.models[0].model_type = AsrModelType::Paraformer {
+            encoder: "encoder.onnx".to_string(),
+            decoder: "decoder.onnx".to_string(),
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_recognizer_options_partial_json() {
+        let options: RecognizerOptions =
+            serde_json::from_str(r#"{"rule1_min_trailing_silence": 0.8}"#).unwrap();
+        assert_eq!(options.rule1_min_trailing_silence, 0.8);
+        assert_eq!(options.rule2_min_trailing_silence, 1.2);
+        assert_eq!(options.decoding_method, DecodingMethod::GreedySearch);
+    }
+
+    #[test]
+    fn test_hotwords_buffer() {
+        let hotwords = vec![
+            Hotword {
+                phrase: " 语音识别 ".to_string(),
+                boost: Some(2.0),
+            },
+            Hotword {
+                phrase: "LIVE SUBTITLES".to_string(),
+                boost: None,
+            },
+        ];
+        assert_eq!(
+            hotwords_buffer(&hotwords, 1.5),
+            "语音识别 :2\nLIVE SUBTITLES :1.5"
+        );
+
+        let mut config = AppConfig {
+            hotwords,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        config.hotwords.push(Hotword {
+            phrase: "a:b".to_string(),
+            boost: None,
+        });
+        assert!(config.validate().is_err());
+        // 无效的热词不交给识别器
+        assert_eq!(hotwords_buffer(&config.hotwords, 1.5).lines().count(), 2);
+    }
+
     #[test]
     fn test_switch_model() {
         let mut config = AppConfig::default();
         assert!(!config.switch_model("non-existent"));
         assert!(config.switch_model("default"));
     }
+
+    /// 每个版本的配置样例都描述同一份用户设置，迁移后必须完全保留
+    fn assert_fixture_settings(config: &AppConfig) {
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.current_model_id, "zipformer-zh");
+        assert_eq!(config.audio_source_type, AudioSourceType::Microphone);
+        assert_eq!(config.audio_device_id, "input:1:USB Microphone");
+
+        let model = config.current_model().expect("current model missing");
+        assert_eq!(model.name, "sherpa-onnx-streaming-zipformer-zh");
+        assert_eq!(model.tokens, "tokens.txt");
+        assert_eq!(model.num_threads, 4);
+        assert_eq!(model.options, RecognizerOptions::default());
+        assert_eq!(
+            model.model_type,
+            AsrModelType::Transducer {
+                encoder: "encoder.int8.onnx".to_string(),
+                decoder: "decoder.onnx".to_string(),
+                joiner: "joiner.int8.onnx".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_migrate_all_fixture_versions() {
+        let fixtures_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/config");
+
+        // 每个版本都必须有对应的样例文件
+        for version in 0..=CONFIG_VERSION {
+            let path = fixtures_dir.join(format!("v{}.json", version));
+            let content = std::fs::read_to_string(&path)
+                .unwrap_or_else(|e| panic!("missing fixture {}: {}", path.display(), e));
+
+            let (config, from_version) = AppConfig::from_json(&content)
+                .unwrap_or_else(|e| panic!("failed to migrate {}: {}", path.display(), e));
+            assert_eq!(from_version, version);
+            assert_fixture_settings(&config);
+        }
+    }
+
+    #[test]
+    fn test_migration_is_idempotent() {
+        let content = include_str!("../tests/fixtures/config/v0.json");
+        let (config, _) = AppConfig::from_json(content).unwrap();
+
+        let saved = serde_json::to_string(&config).unwrap();
+        let (reloaded, from_version) = AppConfig::from_json(&saved).unwrap();
+        assert_eq!(from_version, CONFIG_VERSION);
+        assert_fixture_settings(&reloaded);
+    }
+
+    #[test]
+    fn test_migrate_v1_paraformer() {
+        let content = include_str!("../tests/fixtures/config/v1-paraformer.json");
+        let (config, from_version) = AppConfig::from_json(content).unwrap();
+        assert_eq!(from_version, 1);
+
+        // 旧的 model 作为 encoder，decoder 取同一文件夹中对应的文件
+        assert_eq!(
+            config.models[0].model_type,
+            AsrModelType::Paraformer {
+                encoder: "sherpa-onnx-streaming-paraformer-bilingual-zh-en/encoder.int8.onnx"
+                    .to_string(),
+                decoder: "sherpa-onnx-streaming-paraformer-bilingual-zh-en/decoder.int8.onnx"
+                    .to_string(),
+            }
+        );
+        // 推断不出 decoder 时留空，模型显示为缺少 decoder
+        assert_eq!(
+            config.models[1].model_type,
+            AsrModelType::Paraformer {
+                encoder: "sherpa-onnx-paraformer-zh/model.int8.onnx".to_string(),
+                decoder: String::new(),
+            }
+        );
+        assert!(config.models[1]
+            .missing_files(Path::new("."))
+            .contains(&"decoder".to_string()));
+    }
+
+    #[test]
+    fn test_decoder_for_encoder() {
+        assert_eq!(decoder_for_encoder("encoder.onnx"), "decoder.onnx");
+        assert_eq!(
+            decoder_for_encoder("/models/encoder-dir/encoder-epoch-99.int8.onnx"),
+            "/models/encoder-dir/decoder-epoch-99.int8.onnx"
+        );
+        assert_eq!(
+            decoder_for_encoder("C:\\models\\encoder.onnx"),
+            "C:\\models\\decoder.onnx"
+        );
+        assert_eq!(decoder_for_encoder("encoder-dir/model.onnx"), "");
+    }
+
+    #[test]
+    fn test_migrations_cover_every_version() {
+        assert_eq!(MIGRATIONS.len(), CONFIG_VERSION as usize);
+    }
+
+    #[test]
+    fn test_scan_detects_model_kind() {
+        let root = TempDir::new("scan-kind");
+
+        let create = |dir: &str, files: &[&str]| {
+            let dir = root.join(dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            for file in files {
+                std::fs::write(dir.join(file), b"").unwrap();
+            }
+            ScannedModelFiles::scan_directory(&dir).unwrap()
+        };
+
+        let transducer = create(
+            "zipformer",
+            &["encoder.onnx", "decoder.onnx", "joiner.onnx", "tokens.txt"],
+        );
+        assert_eq!(transducer.model_kind, ModelKind::Transducer);
+        assert!(transducer.is_complete);
+
+        let paraformer = create(
+            "paraformer",
+            &["encoder.int8.onnx", "decoder.int8.onnx", "tokens.txt"],
+        );
+        assert_eq!(paraformer.model_kind, ModelKind::Paraformer);
+        assert!(paraformer.is_complete);
+
+        // 单一模型文件按元数据识别
+        let create_single = |dir: &str, files: &[(&str, &[u8])]| {
+            let dir = root.join(dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            for (file, content) in files {
+                std::fs::write(dir.join(file), content).unwrap();
+            }
+            std::fs::write(dir.join("tokens.txt"), b"").unwrap();
+            ScannedModelFiles::scan_directory(&dir).unwrap()
+        };
+        let zipformer2_ctc =
+            fake_model(&[("model_type", "zipformer2"), ("decode_chunk_len", "32")]);
+        let nemo_ctc = fake_model(&[
+            ("model_type", "EncDecHybridRNNTCTCBPEModel"),
+            ("cache_last_channel_dim1", "70"),
+        ]);
+        let sense_voice = fake_model(&[("model_type", "sense_voice_ctc")]);
+        let offline_ctc = fake_model(&[("model_type", "zipformer2"), ("is_streaming", "0")]);
+
+        let ctc = create_single(
+            "zipformer2-ctc",
+            &[
+                ("ctc.onnx", &zipformer2_ctc),
+                ("ctc.int8.onnx", &zipformer2_ctc),
+                ("HLG.fst", b""),
+                ("silero_vad.onnx", &fake_model(&[])),
+            ],
+        );
+        assert_eq!(ctc.model_kind, ModelKind::Zipformer2Ctc);
+        assert!(ctc.model.as_deref().unwrap().ends_with("ctc.int8.onnx"));
+        assert!(ctc.hlg_graph.is_some());
+        assert!(ctc.is_complete);
+
+        // 按 encoder 的元数据区分 Whisper、Paraformer 和缺少 joiner 的 transducer
+        let create_with_metadata = |dir: &str, metadata: &[(&str, &str)]| {
+            let dir = root.join(dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("encoder.onnx"), fake_model(metadata)).unwrap();
+            std::fs::write(dir.join("decoder.onnx"), fake_model(&[])).unwrap();
+            std::fs::write(dir.join("tokens.txt"), b"").unwrap();
+            ScannedModelFiles::scan_directory(&dir).unwrap()
+        };
+
+        let whisper = create_with_metadata("asr-model", &[("model_type", "whisper-tiny.en")]);
+        assert_eq!(whisper.model_kind, ModelKind::Whisper);
+        assert!(whisper.is_complete);
+        assert!(whisper.to_model_config().unwrap().model_type.is_offline());
+
+        let paraformer = create_with_metadata("streaming-asr", &[("lfr_window_size", "7")]);
+        assert_eq!(paraformer.model_kind, ModelKind::Paraformer);
+
+        let no_joiner =
+            create_with_metadata("zipformer-no-joiner", &[("model_type", "zipformer2")]);
+        assert_eq!(no_joiner.model_kind, ModelKind::Transducer);
+        assert!(!no_joiner.is_complete);
+        assert_eq!(no_joiner.missing_files(), vec!["joiner"]);
+
+        // NeMo 模型不依赖文件夹名称
+        let nemo = create_single("streaming-ctc", &[("model.onnx", &nemo_ctc)]);
+        assert_eq!(nemo.model_kind, ModelKind::NemoCtc);
+        assert!(nemo.is_complete);
+
+        let sense_voice = create_single("asr-offline", &[("model.int8.onnx", &sense_voice)]);
+        assert_eq!(sense_voice.model_kind, ModelKind::SenseVoice);
+        assert!(sense_voice
+            .to_model_config()
+            .unwrap()
+            .model_type
+            .is_offline());
+
+        // VAD 模型不作为识别模型
+        let vad_only = create_single("vad-only", &[("silero_vad.onnx", &zipformer2_ctc)]);
+        assert!(vad_only.model.is_none());
+        assert!(!vad_only.is_complete);
+
+        // 离线 CTC 或无法识别的文件不使用
+        for files in [
+            &[("model.onnx", offline_ctc.as_slice())][..],
+            &[("model.onnx", b"".as_slice())],
+        ] {
+            let unknown = create_single("unknown-ctc", files);
+            assert!(unknown.model.is_none());
+            assert!(!unknown.is_complete);
+            assert_eq!(unknown.missing_files(), vec!["model"]);
+            let _ = std::fs::remove_dir_all(root.join("unknown-ctc"));
+        }
+
+        let model = ctc.to_model_config().unwrap();
+        assert_eq!(model.id, "zipformer2-ctc");
+        assert!(model.model_type.hlg_graph().is_some());
+        assert!(model.validate().is_ok());
+
+        let incomplete = create("incomplete", &["encoder.onnx", "decoder.onnx"]);
+        assert!(incomplete.to_model_config().is_none());
+        assert_eq!(incomplete.missing_files(), vec!["tokens"]);
+        assert!(transducer.missing_files().is_empty());
+    }
+
+    #[test]
+    fn test_missing_model_files() {
+        let dir = TempDir::new("missing-files");
+        for file in ["encoder.onnx", "decoder.onnx", "tokens.txt"] {
+            std::fs::write(dir.join(file), b"").unwrap();
+        }
+
+        let mut model = AppConfig::default().models.remove(0);
+        model.model_type = AsrModelType::Transducer {
+            encoder: "encoder.onnx".to_string(),
+            decoder: "decoder.onnx".to_string(),
+            joiner: "joiner.onnx".to_string(),
+        };
+        model.tokens = "tokens.txt".to_string();
+        assert_eq!(model.missing_files(&dir), vec!["joiner"]);
+
+        // 迁移后留空的文件同样视为缺失
+        model.model_type = AsrModelType::Paraformer {
+            encoder: "encoder.onnx".to_string(),
+            decoder: String::new(),
+        };
+        assert_eq!(model.missing_files(&dir), vec!["decoder"]);
+
+        std::fs::write(dir.join("joiner.onnx"), b"").unwrap();
+        model.model_type = AsrModelType::Transducer {
+            encoder: "encoder.onnx".to_string(),
+            decoder: "decoder.onnx".to_string(),
+            joiner: "joiner.onnx".to_string(),
+        };
+        assert!(model.missing_files(&dir).is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = TempDir::new("save-load");
+        // 保存时创建所在的目录
+        let path = dir.join("config").join(CONFIG_FILE_NAME);
+
+        let config = AppConfig {
+            audio_source_type: AudioSourceType::Microphone,
+            audio_device_id: "input:0:mic".to_string(),
+            ..Default::default()
+        };
+        config.save(&path).unwrap();
+
+        let loaded = AppConfig::load(&path);
+        assert_eq!(loaded.audio_source_type, AudioSourceType::Microphone);
+        assert_eq!(loaded.audio_device_id, "input:0:mic");
+    }
+
+    #[test]
+    fn test_load_corrupt_falls_back_to_default() {
+        let dir = TempDir::new("corrupt");
+        let path = dir.join(CONFIG_FILE_NAME);
+        std::fs::write(&path, "{ not json").unwrap();
+
+        let loaded = AppConfig::load(&path);
+        assert_eq!(loaded.current_model_id, "default");
+        assert!(!path.exists());
+        assert!(path.with_extension("json.corrupt").exists());
+    }
 }
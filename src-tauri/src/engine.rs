@@ -0,0 +1,776 @@
+//! 识别引擎
+//!
+//! 与图形界面无关的识别流程：从音频源读取音频送入识别器，把字幕事件交给调用方提供的
+//! 接收端 (回调或通道)。应用的 Tauri 命令和命令行工具都基于 `Engine`，
+//! 其他 Rust 程序也可以直接嵌入实时字幕：
+//!
+//! ```no_run
+//! use live_subtitles_lib::audio_file::{AudioFileSource, PlaybackPace};
+//! use live_subtitles_lib::engine::{Engine, EngineEvent};
+//! use live_subtitles_lib::recognizer::RecognizerConfig;
+//!
+//! # fn run(config: RecognizerConfig) -> Result<(), live_subtitles_lib::AppError> {
+//! let engine = Engine::new();
+//! let (tx, rx) = std::sync::mpsc::channel();
+//! let source = AudioFileSource::new("talk.wav", PlaybackPace::AsFastAsPossible);
+//! engine.start(config, source, tx)?;
+//!
+//! // 识别结束后接收端被释放，循环随之结束
+//! for event in rx {
+//!     if let EngineEvent::Subtitle(subtitle) = event {
+//!         if subtitle.is_final {
+//!             println!("{}", subtitle.text);
+//!         }
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::audio_source::{AudioReceiver, AudioSource};
+use crate::error::AppError;
+use crate::offline_asr::{OfflineRecognizer, SpeechSegment};
+use crate::online_asr::RecognitionResult;
+use crate::recognizer::{LoadedRecognizer, RecognizerCache, RecognizerConfig, StreamingRecognizer};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// 等待音频时检查是否结束输入的间隔
+const RECV_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// 最后一个 token 的估计时长 (秒)，token 时间只有开始时间
+const LAST_TOKEN_DURATION: f32 = 0.3;
+
+/// 识别状态
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", content = "message", rename_all = "snake_case")]
+pub enum RecognitionState {
+    /// 未开始
+    #[default]
+    Idle,
+    /// 正在加载模型
+    Loading,
+    /// 正在识别
+    Running,
+    /// 已暂停 (保留音频设备和识别器，丢弃输入的音频)
+    Paused,
+    /// 识别出错
+    Error(String),
+}
+
+impl RecognitionState {
+    /// 是否处于一次识别过程中 (加载、识别或暂停)
+    pub fn is_active(&self) -> bool {
+        matches!(self, Self::Loading | Self::Running | Self::Paused)
+    }
+}
+
+/// 字幕事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubtitleEvent {
+    /// 识别的文本
+    pub text: String,
+    /// 是否是句子结束 (endpoint)
+    pub is_final: bool,
+    /// 时间戳 (毫秒)
+    pub timestamp: u64,
+    /// 语句在音频中的开始时间 (秒，相对识别开始)
+    pub start_time: f32,
+    /// 语句在音频中的结束时间 (秒，相对识别开始；中间结果为当前位置)
+    pub end_time: f32,
+    /// 识别出的 token
+    pub tokens: Vec<String>,
+    /// 每个 token 的开始时间 (秒，相对识别开始；模型不支持时为空)
+    pub token_timestamps: Vec<f32>,
+}
+
+impl SubtitleEvent {
+    /// 根据识别结果创建事件，`offset` 为语句开始送入识别器的位置，`end_time` 为已送入的位置 (秒)
+    ///
+    /// 有 token 时间时语句从第一个 token 开始，最终结果在最后一个 token 之后结束，
+    /// 不包括语句前后的静音；模型不支持 token 时间时使用送入的音频位置
+    pub fn new(result: &RecognitionResult, is_final: bool, offset: f32, end_time: f32) -> Self {
+        // token 时间从相对语句开始转换为相对识别开始
+        let token_timestamps: Vec<f32> = result.timestamps.iter().map(|t| offset + t).collect();
+        let start_time = token_timestamps.first().copied().unwrap_or(offset);
+        let end_time = match token_timestamps.last() {
+            Some(last) if is_final => (last + LAST_TOKEN_DURATION).min(end_time).max(start_time),
+            _ => end_time,
+        };
+
+        Self {
+            text: result.text.clone(),
+            is_final,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64,
+            start_time,
+            end_time,
+            tokens: result.tokens.clone(),
+            token_timestamps,
+        }
+    }
+}
+
+/// 引擎发出的事件
+#[derive(Debug, Clone)]
+pub enum EngineEvent {
+    /// 字幕 (中间结果或最终结果)
+    Subtitle(SubtitleEvent),
+    /// 识别状态变化 (出错时为 `RecognitionState::Error`)
+    State(RecognitionState),
+    /// 识别出错，随后发送 `State(RecognitionState::Error)`
+    Error(Arc<AppError>),
+    /// 开始/结束加载模型 (识别器已缓存时不发送)
+    ModelLoading(bool),
+    /// 音频源自然结束，全部音频已识别
+    Finished,
+}
+
+/// 事件接收端
+///
+/// 闭包和 `mpsc::Sender<EngineEvent>` 都可以作为接收端。
+/// 事件在识别线程或调用控制方法的线程中发送，接收端内不应长时间阻塞
+pub trait EventSink: Send + 'static {
+    /// 接收一个事件
+    fn send(&mut self, event: EngineEvent);
+}
+
+impl<F> EventSink for F
+where
+    F: FnMut(EngineEvent) + Send + 'static,
+{
+    fn send(&mut self, event: EngineEvent) {
+        self(event)
+    }
+}
+
+impl EventSink for Sender<EngineEvent> {
+    fn send(&mut self, event: EngineEvent) {
+        // 接收方已经不再读取时丢弃事件
+        let _ = Sender::send(self, event);
+    }
+}
+
+type SharedSink = Arc<Mutex<Box<dyn EventSink>>>;
+
+fn emit(sink: &SharedSink, event: EngineEvent) {
+    if let Ok(mut sink) = sink.lock() {
+        sink.send(event);
+    }
+}
+
+/// 当前识别会话
+struct Session {
+    /// 会话编号 (与 `Status::session` 相同时为当前会话)
+    id: u64,
+    source: Box<dyn AudioSource>,
+    sink: SharedSink,
+    /// 不再读取音频 (结束输入或停止识别)
+    draining: Arc<AtomicBool>,
+}
+
+/// 识别引擎
+///
+/// 同一时间只运行一个会话。停止识别后识别器仍保留，
+/// 下次开始识别时如果配置未变化则直接复用，无需重新加载模型
+#[derive(Clone, Default)]
+pub struct Engine {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    recognizer: RecognizerCache,
+    status: Mutex<Status>,
+    session: Mutex<Option<Session>>,
+    /// 最近一个会话的识别线程
+    worker: Mutex<Option<JoinHandle<()>>>,
+    /// 流式识别使用的热词 (见 `Engine::set_hotwords`)
+    hotwords: Mutex<String>,
+}
+
+#[derive(Default)]
+struct Status {
+    state: RecognitionState,
+    /// 当前会话编号，每次开始识别时加一。
+    /// 已被停止的识别线程可能还没有退出，它只能修改自己会话的状态，不影响新的会话
+    session: u64,
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 当前识别状态
+    pub fn state(&self) -> RecognitionState {
+        self.inner.status().state.clone()
+    }
+
+    /// 是否处于一次识别过程中 (加载、识别或暂停)
+    pub fn is_active(&self) -> bool {
+        self.inner.status().state.is_active()
+    }
+
+    /// 设置热词 (每行一个，见 `recognizer::hotwords_for`)
+    ///
+    /// 识别过程中修改时从下一句开始生效，不需要重新加载识别器
+    pub fn set_hotwords(&self, hotwords: String) {
+        *self.inner.hotwords() = hotwords;
+    }
+
+    /// 预加载识别器 (避免第一次开始识别时卡顿)，`on_loading` 与 `EngineEvent::ModelLoading` 相同
+    pub fn preload(
+        &self,
+        config: RecognizerConfig,
+        on_loading: impl FnMut(bool),
+    ) -> Result<(), AppError> {
+        self.inner.recognizer.preload(&config, on_loading)
+    }
+
+    /// 开始识别
+    ///
+    /// 音频源在当前线程打开，以便立即返回错误；模型加载与识别在后台线程进行。
+    /// 会话结束 (停止、出错或音频源结束) 后释放接收端
+    pub fn start(
+        &self,
+        config: RecognizerConfig,
+        source: impl AudioSource + 'static,
+        sink: impl EventSink,
+    ) -> Result<(), AppError> {
+        let sample_rate = config.sample_rate();
+        self.start_session(
+            RecognizerSource::Config(Box::new(config)),
+            sample_rate,
+            source,
+            sink,
+        )
+    }
+
+    /// 使用调用方创建的识别器开始识别 (不经过缓存，例如自定义的 `StreamingRecognizer`)
+    pub fn start_with_recognizer(
+        &self,
+        recognizer: LoadedRecognizer,
+        source: impl AudioSource + 'static,
+        sink: impl EventSink,
+    ) -> Result<(), AppError> {
+        let sample_rate = recognizer.sample_rate();
+        let recognizer = RecognizerSource::Loaded(Box::new(recognizer));
+        self.start_session(recognizer, sample_rate, source, sink)
+    }
+
+    fn start_session(
+        &self,
+        recognizer: RecognizerSource,
+        sample_rate: u32,
+        mut source: impl AudioSource + 'static,
+        sink: impl EventSink,
+    ) -> Result<(), AppError> {
+        // 同一时间只有一个调用可以开始识别
+        let mut worker = self.inner.worker();
+        if self.is_active() {
+            return Err(AppError::InvalidState("识别已在运行".to_string()));
+        }
+
+        // 刚停止的会话的识别线程可能还在处理最后一块音频，等它退出并放回识别器
+        if let Some(previous) = worker.take() {
+            join_worker(previous);
+        }
+
+        eprintln!("Starting audio source: {}", source.describe());
+        let audio_rx = source.start(sample_rate)?;
+        let sink: SharedSink = Arc::new(Mutex::new(Box::new(sink)));
+        let draining = Arc::new(AtomicBool::new(false));
+
+        // 标记为加载中 (识别线程准备好识别器后切换为运行中)
+        let id = self.inner.begin_session()?;
+        emit(&sink, EngineEvent::State(RecognitionState::Loading));
+
+        *self.inner.session() = Some(Session {
+            id,
+            source: Box::new(source),
+            sink: Arc::clone(&sink),
+            draining: Arc::clone(&draining),
+        });
+
+        let session = SessionHandle {
+            inner: Arc::clone(&self.inner),
+            id,
+        };
+        *worker = Some(thread::spawn(move || {
+            run_session(&session, recognizer, audio_rx, &sink, &draining);
+            session.end();
+        }));
+
+        Ok(())
+    }
+
+    /// 停止识别，丢弃尚未结束的语句
+    ///
+    /// 识别线程在后台退出，下次开始识别时先等待它退出再复用识别器
+    pub fn stop(&self) -> Result<(), AppError> {
+        self.inner.transition(RecognitionState::Idle, |_| true)?;
+
+        let session = self.inner.session().take();
+        if let Some(mut session) = session {
+            // 调用方提供的通道 (ChannelSource) 不会因停止而关闭，识别线程需要主动结束读取
+            session.draining.store(true, Ordering::SeqCst);
+            session.source.stop();
+            emit(&session.sink, EngineEvent::State(RecognitionState::Idle));
+        }
+        Ok(())
+    }
+
+    /// 结束音频输入：识别完已送入识别器的音频后，像音频源结束一样输出最后的结果
+    pub fn finish(&self) {
+        if let Some(session) = self.inner.session().as_ref() {
+            session.draining.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// 暂停识别 (保留音频设备和识别器，恢复时无需重新加载)
+    pub fn pause(&self) -> Result<(), AppError> {
+        self.set_paused(true)
+    }
+
+    /// 恢复识别
+    pub fn resume(&self) -> Result<(), AppError> {
+        self.set_paused(false)
+    }
+
+    fn set_paused(&self, paused: bool) -> Result<(), AppError> {
+        let (next, current) = if paused {
+            (RecognitionState::Paused, RecognitionState::Running)
+        } else {
+            (RecognitionState::Running, RecognitionState::Paused)
+        };
+        self.inner.transition(next.clone(), |s| *s == current)?;
+
+        let sink = self.inner.session().as_ref().map(|session| {
+            session.source.set_paused(paused);
+            Arc::clone(&session.sink)
+        });
+        if let Some(sink) = sink {
+            emit(&sink, EngineEvent::State(next));
+        }
+        Ok(())
+    }
+}
+
+impl Inner {
+    fn status(&self) -> MutexGuard<'_, Status> {
+        self.status.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn session(&self) -> MutexGuard<'_, Option<Session>> {
+        self.session.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn worker(&self) -> MutexGuard<'_, Option<JoinHandle<()>>> {
+        self.worker.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn hotwords(&self) -> MutexGuard<'_, String> {
+        self.hotwords.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 切换识别状态，`allowed` 检查当前状态，不允许切换时返回错误且状态不变
+    ///
+    /// 状态变化由调用方发送给接收端 (不在持有状态锁时调用接收端)
+    fn transition(
+        &self,
+        next: RecognitionState,
+        allowed: impl FnOnce(&RecognitionState) -> bool,
+    ) -> Result<(), AppError> {
+        Self::switch(&mut self.status(), next, allowed)
+    }
+
+    fn switch(
+        status: &mut Status,
+        next: RecognitionState,
+        allowed: impl FnOnce(&RecognitionState) -> bool,
+    ) -> Result<(), AppError> {
+        if !allowed(&status.state) {
+            return Err(AppError::InvalidState(format!(
+                "无法从 {:?} 状态切换到 {:?} 状态",
+                status.state, next
+            )));
+        }
+        status.state = next;
+        Ok(())
+    }
+
+    /// 切换为加载中并分配新的会话编号
+    fn begin_session(&self) -> Result<u64, AppError> {
+        let mut status = self.status();
+        Self::switch(&mut status, RecognitionState::Loading, |s| !s.is_active())?;
+        status.session += 1;
+        Ok(status.session)
+    }
+}
+
+/// 识别线程使用的会话句柄
+///
+/// 会话被停止或被新的会话替换后，所有操作都不再生效
+struct SessionHandle {
+    inner: Arc<Inner>,
+    id: u64,
+}
+
+impl SessionHandle {
+    /// 切换本会话的状态，会话已结束或不允许切换时返回 false
+    fn transition(
+        &self,
+        next: RecognitionState,
+        allowed: impl FnOnce(&RecognitionState) -> bool,
+    ) -> bool {
+        let mut status = self.inner.status();
+        status.session == self.id && Inner::switch(&mut status, next, allowed).is_ok()
+    }
+
+    /// 识别循环中检查是否继续：返回 None 表示已停止，Some(false) 表示暂停中
+    fn should_process(&self) -> Option<bool> {
+        let status = self.inner.status();
+        if status.session != self.id {
+            return None;
+        }
+        match status.state {
+            RecognitionState::Running => Some(true),
+            RecognitionState::Paused => Some(false),
+            _ => None,
+        }
+    }
+
+    /// 取出音频源提前结束的错误 (见 `AudioSource::take_error`)
+    fn take_source_error(&self) -> Option<AppError> {
+        let mut session = self.inner.session();
+        session
+            .as_mut()
+            .filter(|session| session.id == self.id)
+            .and_then(|session| session.source.take_error())
+    }
+
+    /// 识别线程结束时释放会话 (已被停止或新的会话替换时不处理)
+    fn end(&self) {
+        let mut session = self.inner.session();
+        if session
+            .as_ref()
+            .is_some_and(|session| session.id == self.id)
+        {
+            *session = None;
+        }
+    }
+}
+
+/// 等待识别线程退出 (在识别线程中调用时不等待，例如接收端内开始新的识别)
+fn join_worker(worker: JoinHandle<()>) {
+    if worker.thread().id() != thread::current().id() {
+        let _ = worker.join();
+    }
+}
+
+/// 会话使用的识别器
+enum RecognizerSource {
+    /// 从缓存取出，配置变化时加载模型 (会话结束后放回缓存)
+    Config(Box<RecognizerConfig>),
+    /// 调用方创建的识别器
+    Loaded(Box<LoadedRecognizer>),
+}
+
+/// 识别线程：从缓存取出 (或加载) 识别器，识别结束后放回缓存
+fn run_session(
+    session: &SessionHandle,
+    recognizer: RecognizerSource,
+    audio_rx: AudioReceiver,
+    sink: &SharedSink,
+    draining: &AtomicBool,
+) {
+    match recognizer {
+        RecognizerSource::Config(config) => {
+            let recognizers = &session.inner.recognizer;
+            let loaded = recognizers.take(&config, |loading| {
+                emit(sink, EngineEvent::ModelLoading(loading));
+            });
+            if let Some(recognizer) = recognize(session, loaded, audio_rx, sink, draining) {
+                recognizers.put_back(*config, recognizer);
+            }
+        }
+        RecognizerSource::Loaded(recognizer) => {
+            recognize(session, Ok(*recognizer), audio_rx, sink, draining);
+        }
+    }
+}
+
+/// 识别主循环：持续处理音频直到停止或音频源关闭，返回可以复用的识别器
+fn recognize(
+    session: &SessionHandle,
+    recognizer: Result<LoadedRecognizer, AppError>,
+    audio_rx: AudioReceiver,
+    sink: &SharedSink,
+    draining: &AtomicBool,
+) -> Option<LoadedRecognizer> {
+    let mut recognizer = match recognizer {
+        Ok(recognizer) => recognizer,
+        Err(e) => {
+            eprintln!("Failed to create recognizer: {}", e);
+            fail(session, sink, e);
+            return None;
+        }
+    };
+
+    // 加载期间可能已被停止
+    if !session.transition(RecognitionState::Running, |s| {
+        *s == RecognitionState::Loading
+    }) {
+        return Some(recognizer);
+    }
+    emit(sink, EngineEvent::State(RecognitionState::Running));
+
+    // 复用的识别器可能残留上次停止时未结束的语音
+    recognizer.reset();
+
+    let input = AudioInput {
+        audio_rx: &audio_rx,
+        draining,
+    };
+    let finished = match &mut recognizer {
+        LoadedRecognizer::Online(recognizer) => {
+            run_online(session, recognizer.as_mut(), &input, sink)
+        }
+        LoadedRecognizer::Offline(recognizer) => run_offline(session, recognizer, &input, sink),
+    };
+
+    // 音频源结束时回到空闲状态 (主动停止时 stop 已经切换)，音频源出错时以错误结束
+    if finished {
+        match session.take_source_error() {
+            Some(e) => {
+                eprintln!("Audio source error: {}", e);
+                fail(session, sink, e);
+            }
+            None => {
+                emit(sink, EngineEvent::Finished);
+                if session.transition(RecognitionState::Idle, |s| s.is_active()) {
+                    emit(sink, EngineEvent::State(RecognitionState::Idle));
+                }
+            }
+        }
+    }
+    Some(recognizer)
+}
+
+/// 会话出错结束
+fn fail(session: &SessionHandle, sink: &SharedSink, error: AppError) {
+    let next = RecognitionState::Error(error.to_string());
+    if session.transition(next.clone(), |s| s.is_active()) {
+        emit(sink, EngineEvent::Error(Arc::new(error)));
+        emit(sink, EngineEvent::State(next));
+    }
+}
+
+/// 识别线程读取的音频
+struct AudioInput<'a> {
+    audio_rx: &'a AudioReceiver,
+    draining: &'a AtomicBool,
+}
+
+impl AudioInput<'_> {
+    /// 接收下一段音频，音频源关闭或结束输入时返回 None
+    fn next(&self) -> Option<Vec<f32>> {
+        loop {
+            if self.draining.load(Ordering::SeqCst) {
+                return None;
+            }
+            match self.audio_rx.recv_timeout(RECV_TIMEOUT) {
+                Ok(samples) => return Some(samples),
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => return None,
+            }
+        }
+    }
+}
+
+/// 流式识别：识别器内置 endpoint 检测，实时输出中间结果
+///
+/// 返回 true 表示音频源自然结束，false 表示被停止
+fn run_online(
+    session: &SessionHandle,
+    recognizer: &mut dyn StreamingRecognizer,
+    input: &AudioInput,
+    sink: &SharedSink,
+) -> bool {
+    let sample_rate = recognizer.sample_rate() as f32;
+    let mut last_result = RecognitionResult::default();
+    let mut stopped = false;
+    // 已收到的样本数 (包括暂停期间丢弃的)，以及当前语句开始时的样本数 (用于计算语句在音频中的位置)
+    let mut samples_fed: u64 = 0;
+    let mut utterance_start: u64 = 0;
+    // 暂停开始时的样本数
+    let mut paused_at: Option<u64> = None;
+    // 识别流当前使用的热词 (复用的识别器可能保留着上次识别的热词)
+    let mut hotwords = session.inner.hotwords().clone();
+    recognizer.set_hotwords(&hotwords);
+
+    // 循环处理音频
+    while let Some(samples) = input.next() {
+        // 检查是否仍在运行，暂停时丢弃音频，但仍计入音频位置
+        match session.should_process() {
+            Some(true) => {}
+            Some(false) => {
+                paused_at.get_or_insert(samples_fed);
+                samples_fed += samples.len() as u64;
+                continue;
+            }
+            None => {
+                stopped = true;
+                break;
+            }
+        }
+
+        // 恢复识别：暂停前未结束的语句作为最终结果发送，
+        // 并重新开始识别流，使之后的时间与音频位置一致
+        if let Some(paused_at) = paused_at.take() {
+            if !last_result.text.is_empty() {
+                let event = SubtitleEvent::new(
+                    &last_result,
+                    true,
+                    utterance_start as f32 / sample_rate,
+                    paused_at as f32 / sample_rate,
+                );
+                emit(sink, EngineEvent::Subtitle(event));
+                last_result = RecognitionResult::default();
+            }
+            recognizer.reset();
+            utterance_start = samples_fed;
+        }
+
+        // 处理音频
+        let (result, is_endpoint) = recognizer.process(&samples);
+        samples_fed += samples.len() as u64;
+
+        let offset = utterance_start as f32 / sample_rate;
+        let end_time = samples_fed as f32 / sample_rate;
+
+        // 如果有新文本，发送更新
+        if !result.text.is_empty() && result.text != last_result.text {
+            // 中间结果，不是最终的
+            let event = SubtitleEvent::new(&result, false, offset, end_time);
+            emit(sink, EngineEvent::Subtitle(event));
+            last_result = result;
+        }
+
+        // 如果到达 endpoint，发送最终结果并重置流
+        if is_endpoint && !last_result.text.is_empty() {
+            // 发送最终结果
+            let event = SubtitleEvent::new(&last_result, true, offset, end_time);
+            emit(sink, EngineEvent::Subtitle(event));
+
+            recognizer.reset();
+            last_result = RecognitionResult::default();
+            utterance_start = samples_fed;
+        } else if is_endpoint {
+            // 没有文本但检测到 endpoint，只重置
+            recognizer.reset();
+            utterance_start = samples_fed;
+        }
+
+        // 热词在语句之间更新
+        if is_endpoint {
+            let latest = session.inner.hotwords();
+            if *latest != hotwords {
+                hotwords = latest.clone();
+                drop(latest);
+                recognizer.set_hotwords(&hotwords);
+            }
+        }
+    }
+
+    // 停止识别会关闭音频通道，因此通道关闭后还要再检查一次状态
+    if stopped || session.should_process().is_none() {
+        return false;
+    }
+
+    // 音频源自然结束 (如文件读完)，把剩余文本作为最终结果发送 (暂停中结束时到暂停为止)
+    if !last_result.text.is_empty() {
+        let event = SubtitleEvent::new(
+            &last_result,
+            true,
+            utterance_start as f32 / sample_rate,
+            paused_at.unwrap_or(samples_fed) as f32 / sample_rate,
+        );
+        emit(sink, EngineEvent::Subtitle(event));
+    }
+    true
+}
+
+/// 离线识别：Silero VAD 切分语音段，每段用离线模型解码后输出最终结果
+///
+/// 返回值与 `run_online` 相同
+fn run_offline(
+    session: &SessionHandle,
+    recognizer: &mut OfflineRecognizer,
+    input: &AudioInput,
+    sink: &SharedSink,
+) -> bool {
+    let sample_rate = recognizer.sample_rate() as f32;
+    let mut stopped = false;
+    // VAD 只计算送入的音频，暂停期间丢弃的样本数加到语音段的开始时间上
+    let mut samples_skipped: u64 = 0;
+    let mut paused = false;
+
+    // 循环处理音频，每个语音段结束后直接输出最终结果
+    while let Some(samples) = input.next() {
+        // 检查是否仍在运行，暂停时丢弃音频，但仍计入音频位置
+        match session.should_process() {
+            Some(true) => {}
+            Some(false) => {
+                if !paused {
+                    // 暂停前未结束的语音段不与恢复后的音频拼接
+                    paused = true;
+                    let offset = samples_skipped as f32 / sample_rate;
+                    for segment in recognizer.flush() {
+                        emit_speech_segment(sink, &segment, offset);
+                    }
+                }
+                samples_skipped += samples.len() as u64;
+                continue;
+            }
+            None => {
+                stopped = true;
+                break;
+            }
+        }
+        paused = false;
+
+        let offset = samples_skipped as f32 / sample_rate;
+        for segment in recognizer.process(&samples) {
+            emit_speech_segment(sink, &segment, offset);
+        }
+    }
+
+    // 停止识别会关闭音频通道，因此通道关闭后还要再检查一次状态
+    if stopped || session.should_process().is_none() {
+        return false;
+    }
+
+    // 音频源自然结束 (如文件读完)，识别最后一个未结束的语音段
+    let offset = samples_skipped as f32 / sample_rate;
+    for segment in recognizer.flush() {
+        emit_speech_segment(sink, &segment, offset);
+    }
+    true
+}
+
+/// 发送离线识别的一个语音段 (总是最终结果)
+///
+/// `offset` 为暂停期间丢弃的音频时长 (秒)
+fn emit_speech_segment(sink: &SharedSink, segment: &SpeechSegment, offset: f32) {
+    let start = segment.start + offset;
+    let event = SubtitleEvent::new(&segment.result, true, start, start + segment.duration);
+    emit(sink, EngineEvent::Subtitle(event));
+}
@@ -0,0 +1,208 @@
+//! 错误类型
+//!
+//! Tauri 命令和识别引擎返回 `AppError`，序列化给前端时为 `{code, message, details}`：
+//! `code` 区分错误类别，前端据此给出解决办法 (例如选择其他音频设备、补全模型文件)；
+//! `details` 是与类别相关的附加信息，没有时为 null。
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::json;
+use std::path::PathBuf;
+
+/// 应用错误
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    /// 配置无效 (参数超出范围、格式错误等)
+    #[error("{0}")]
+    Config(String),
+
+    /// 没有选中任何模型
+    #[error("未配置语音识别模型")]
+    ModelNotConfigured,
+
+    /// 模型文件缺失，`files` 为缺失文件的用途 (encoder/decoder/joiner/tokens 等)
+    #[error("模型 {model} 缺少文件: {}", files.join(", "))]
+    ModelFilesMissing {
+        model: String,
+        model_dir: PathBuf,
+        files: Vec<String>,
+    },
+
+    /// 模型文件存在但无法使用 (格式错误、与配置不一致)，`problems` 为 (文件用途, 原因)
+    #[error("模型 {model} 的文件有误: {}", describe_problems(problems))]
+    ModelFilesInvalid {
+        model: String,
+        problems: Vec<(String, String)>,
+    },
+
+    /// 音频设备无法打开或已断开，`device` 为 None 表示默认设备
+    #[error("{message}")]
+    AudioDevice {
+        device: Option<String>,
+        message: String,
+    },
+
+    /// 音频文件无法读取或解码
+    #[error("{message}")]
+    AudioFile { path: PathBuf, message: String },
+
+    /// 识别器创建失败 (模型文件损坏或与模型类型不匹配)
+    #[error("{0}")]
+    Recognizer(String),
+
+    /// 当前状态不允许该操作 (例如识别已在进行中)
+    #[error("{0}")]
+    InvalidState(String),
+
+    /// 请求的对象不存在 (字幕会话、热词、目录等)
+    #[error("{0}")]
+    NotFound(String),
+
+    /// 文件读写失败
+    #[error("无法读写 {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// 字幕推送服务无法启动
+    #[error("{0}")]
+    Server(String),
+
+    /// 其他内部错误 (锁被污染、窗口操作失败等)
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl AppError {
+    /// 文件读写失败
+    pub fn io(path: impl Into<PathBuf>, source: std::io::Error) -> Self {
+        Self::Io {
+            path: path.into(),
+            source,
+        }
+    }
+
+    /// 错误类别 (前端根据类别提供解决办法)
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Config(_) => "config",
+            Self::ModelNotConfigured => "model_not_configured",
+            Self::ModelFilesMissing { .. } => "model_files_missing",
+            Self::ModelFilesInvalid { .. } => "model_files_invalid",
+            Self::AudioDevice { .. } => "audio_device",
+            Self::AudioFile { .. } => "audio_file",
+            Self::Recognizer(_) => "recognizer",
+            Self::InvalidState(_) => "invalid_state",
+            Self::NotFound(_) => "not_found",
+            Self::Io { .. } => "io",
+            Self::Server(_) => "server",
+            Self::Internal(_) => "internal",
+        }
+    }
+
+    /// 与错误类别相关的附加信息
+    pub fn details(&self) -> serde_json::Value {
+        match self {
+            Self::ModelFilesMissing {
+                model,
+                model_dir,
+                files,
+            } => json!({ "model": model, "model_dir": model_dir, "files": files }),
+            Self::ModelFilesInvalid { model, problems } => json!({
+                "model": model,
+                "problems": problems
+                    .iter()
+                    .map(|(file, reason)| json!({ "file": file, "reason": reason }))
+                    .collect::<Vec<_>>(),
+            }),
+            Self::AudioDevice { device, .. } => json!({ "device": device }),
+            Self::AudioFile { path, .. } => json!({ "path": path }),
+            Self::Io { path, source } => json!({ "path": path, "kind": source.kind().to_string() }),
+            _ => serde_json::Value::Null,
+        }
+    }
+}
+
+fn describe_problems(problems: &[(String, String)]) -> String {
+    problems
+        .iter()
+        .map(|(file, reason)| format!("{} {}", file, reason))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+impl<T> From<std::sync::PoisonError<T>> for AppError {
+    fn from(e: std::sync::PoisonError<T>) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(e: tauri::Error) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_missing_files() {
+        let error = AppError::ModelFilesMissing {
+            model: "zipformer".to_string(),
+            model_dir: PathBuf::from("models/zipformer"),
+            files: vec!["joiner".to_string(), "tokens".to_string()],
+        };
+
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({
+                "code": "model_files_missing",
+                "message": "模型 zipformer 缺少文件: joiner, tokens",
+                "details": {
+                    "model": "zipformer",
+                    "model_dir": "models/zipformer",
+                    "files": ["joiner", "tokens"],
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_serialize_without_details() {
+        let error = AppError::InvalidState("识别已在运行".to_string());
+
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({
+                "code": "invalid_state",
+                "message": "识别已在运行",
+                "details": null,
+            })
+        );
+    }
+
+    #[test]
+    fn test_io_error_keeps_source() {
+        use std::error::Error;
+
+        let source = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied");
+        let error = AppError::io("/tmp/config.json", source);
+
+        assert_eq!(error.code(), "io");
+        assert_eq!(error.details()["path"], "/tmp/config.json");
+        assert!(error.source().is_some());
+    }
+}
@@ -1,52 +1,168 @@
 // Live Subtitles - 实时字幕应用
 // 基于 Tauri v2 + sherpa-rs
+//
+// 识别引擎 (engine)、配置、音频输入、识别器与字幕导出模块是公开的，
+// 供命令行工具 (src/bin/live-subtitles-cli.rs) 和其他 Rust 程序复用。
+// Tauri 命令只负责读取配置并把引擎事件转发给前端
 
 #[cfg(not(target_os = "windows"))]
-mod audio;
+pub mod audio;
+pub mod audio_file;
+pub mod audio_source;
 #[cfg(target_os = "windows")]
-mod audio_wasapi;
-mod config;
-mod online_asr;
+pub mod audio_wasapi;
+mod autosave;
+pub mod config;
+pub mod engine;
+pub mod error;
+pub mod model_manifest;
+pub mod model_registry;
+pub mod model_validation;
+pub mod offline_asr;
+pub mod online_asr;
+pub mod onnx;
+pub mod recognizer;
+mod server;
+#[cfg(test)]
+mod test_util;
+pub mod transcript;
 
 #[cfg(not(target_os = "windows"))]
-use audio::AudioCapture;
+pub use audio::AudioCapture;
+use audio_file::{AudioFileSource, PlaybackPace};
+pub use audio_source::{AudioSource, CaptureMode};
 #[cfg(target_os = "windows")]
-use audio_wasapi::AudioCapture;
+pub use audio_wasapi::AudioCapture;
+use autosave::{AutosaveWriter, SessionInfo};
 use config::AppConfig;
+use config::AutosaveConfig;
+use config::Hotword;
 use config::ScannedModelFiles;
+use config::ServerConfig;
 use cpal::traits::{DeviceTrait, HostTrait};
-use online_asr::{OnlineRecognizer, OnlineRecognizerConfig};
+use engine::{Engine, EngineEvent};
+pub use engine::{RecognitionState, SubtitleEvent};
+pub use error::AppError;
+use model_registry::{ModelDescriptor, ModelRegistry};
+use model_validation::ModelReport;
+use recognizer::RecognizerConfig;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use server::{ModelInfo, ServerStatus, SubtitleServer};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use std::thread;
 use tauri::{
     menu::{Menu, MenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     Emitter, Manager, State, WebviewUrl, WebviewWindowBuilder,
 };
+use transcript::{Transcript, TranscriptEntry, TranscriptFormat, WrapOptions};
 
 /// 应用状态
 pub struct AppState {
     /// 配置
     config: Mutex<AppConfig>,
-    /// 音频捕获 (运行时创建)
-    audio_capture: Mutex<Option<AudioCapture>>,
-    /// 是否正在识别
-    is_running: Mutex<bool>,
+    /// 识别引擎 (停止识别后保留已加载的识别器，避免重复加载模型)
+    engine: Engine,
+    /// 当前识别会话的编号 (每次开始识别时加一)，已停止的会话迟到的事件不再处理
+    session: AtomicU64,
+    /// 本次识别的字幕记录 (最终结果)
+    transcript: Mutex<Transcript>,
+    /// 字幕自动保存 (启用时每次识别创建一个会话)
+    autosave: Mutex<Option<AutosaveWriter>>,
+    /// 字幕推送服务 (WebSocket)
+    server: SubtitleServer,
+    /// 模型注册表 (扫描结果缓存，模型目录变化时自动刷新)
+    models: Arc<ModelRegistry>,
     /// 模型目录
     models_dir: PathBuf,
+    /// 配置文件路径
+    config_path: PathBuf,
+    /// 默认的字幕自动保存目录
+    transcripts_dir: PathBuf,
 }
 
 impl AppState {
-    fn new(models_dir: PathBuf) -> Self {
+    fn new(models_dir: PathBuf, config_path: PathBuf, transcripts_dir: PathBuf) -> Self {
+        let config = AppConfig::load(&config_path);
+        let models = ModelRegistry::new(model_roots(&models_dir, &config));
         Self {
-            config: Mutex::new(AppConfig::default()),
-            audio_capture: Mutex::new(None),
-            is_running: Mutex::new(false),
+            config: Mutex::new(config),
+            engine: Engine::new(),
+            session: AtomicU64::new(0),
+            transcript: Mutex::new(Transcript::default()),
+            autosave: Mutex::new(None),
+            server: SubtitleServer::default(),
+            models: Arc::new(models),
             models_dir,
+            config_path,
+            transcripts_dir,
         }
     }
+
+    /// 修改配置：在副本上修改，校验并保存成功后才替换当前配置
+    ///
+    /// 修改或校验失败时当前配置和配置文件都保持不变
+    fn update_config<T>(
+        &self,
+        update: impl FnOnce(&mut AppConfig) -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        let mut config = self.config.lock()?;
+        let mut updated = config.clone();
+        let result = update(&mut updated)?;
+        updated.validate().map_err(AppError::Config)?;
+        updated.save(&self.config_path)?;
+        *config = updated;
+        Ok(result)
+    }
+
+    /// 把当前模型信息同步到字幕推送服务的握手消息
+    fn refresh_server_model(&self) {
+        let Ok(config) = self.config.lock() else {
+            return;
+        };
+        if let Some(model) = config.current_model() {
+            self.server.set_model(ModelInfo {
+                id: model.id.clone(),
+                name: model.name.clone(),
+                sample_rate: model.sample_rate,
+                offline: model.model_type.is_offline(),
+            });
+        }
+    }
+
+    /// 把当前热词交给识别引擎 (识别过程中修改时从下一句开始生效)
+    fn refresh_hotwords(&self) {
+        if let Ok(config) = self.config.lock() {
+            self.engine.set_hotwords(recognizer::hotwords_for(&config));
+        }
+    }
+
+    /// 按配置更新模型注册表扫描的目录
+    fn refresh_model_roots(&self) -> Result<(), AppError> {
+        let roots = model_roots(&self.models_dir, &*self.config.lock()?);
+        self.models.set_roots(roots)?;
+        Ok(())
+    }
+
+    /// 字幕自动保存目录 (未配置时使用应用数据目录)
+    fn autosave_dir(&self) -> Result<PathBuf, AppError> {
+        let config = self.config.lock()?;
+        if config.autosave.directory.is_empty() {
+            Ok(self.transcripts_dir.clone())
+        } else {
+            Ok(PathBuf::from(&config.autosave.directory))
+        }
+    }
+}
+
+/// 模型注册表扫描的目录：应用的模型目录和设置的模型根目录
+fn model_roots(models_dir: &Path, config: &AppConfig) -> Vec<PathBuf> {
+    let mut roots = vec![models_dir.to_path_buf()];
+    if !config.models_root_dir.is_empty() {
+        roots.push(PathBuf::from(&config.models_root_dir));
+    }
+    roots
 }
 
 /// 音频设备信息
@@ -62,33 +178,9 @@ pub struct AudioDeviceInfo {
     pub is_default: bool,
 }
 
-/// 发送给前端的字幕事件
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SubtitleEvent {
-    /// 识别的文本
-    pub text: String,
-    /// 是否是句子结束 (endpoint)
-    pub is_final: bool,
-    /// 时间戳 (毫秒)
-    pub timestamp: u64,
-}
-
-impl SubtitleEvent {
-    fn new(text: String, is_final: bool) -> Self {
-        Self {
-            text,
-            is_final,
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as u64,
-        }
-    }
-}
-
 /// 枚举所有可用的音频设备
 #[tauri::command]
-async fn enumerate_audio_devices() -> Result<Vec<AudioDeviceInfo>, String> {
+async fn enumerate_audio_devices() -> Result<Vec<AudioDeviceInfo>, AppError> {
     let host = cpal::default_host();
     let mut devices = Vec::new();
 
@@ -98,9 +190,10 @@ async fn enumerate_audio_devices() -> Result<Vec<AudioDeviceInfo>, String> {
     let default_output_name = host.default_output_device().and_then(|d| d.name().ok());
 
     // 枚举所有设备
-    let all_devices = host
-        .devices()
-        .map_err(|e| format!("Failed to enumerate devices: {}", e))?;
+    let all_devices = host.devices().map_err(|e| AppError::AudioDevice {
+        device: None,
+        message: format!("无法枚举音频设备: {}", e),
+    })?;
 
     for (index, device) in all_devices.enumerate() {
         let device_name = device
@@ -126,7 +219,8 @@ async fn enumerate_audio_devices() -> Result<Vec<AudioDeviceInfo>, String> {
         }
 
         // 添加为输出设备（用于 loopback，捕获系统音频）
-        if supports_output {
+        // Linux 录制的是 PulseAudio/PipeWire sink 的 monitor source，输出设备在下面单独枚举
+        if supports_output && !cfg!(target_os = "linux") {
             let is_default = default_output_name.as_ref() == Some(&device_name);
             devices.push(AudioDeviceInfo {
                 id: format!("output:{}", device_id),
@@ -137,52 +231,224 @@ async fn enumerate_audio_devices() -> Result<Vec<AudioDeviceInfo>, String> {
         }
     }
 
+    #[cfg(target_os = "linux")]
+    {
+        let default_sink = audio::default_sink_name().ok();
+        let sinks = audio::list_sinks().map_err(|message| AppError::AudioDevice {
+            device: None,
+            message,
+        })?;
+
+        for (index, sink) in sinks {
+            devices.push(AudioDeviceInfo {
+                id: format!("output:{}:{}", index, sink),
+                name: format!("{} (系统音频)", sink),
+                device_type: "output".to_string(),
+                is_default: default_sink.as_ref() == Some(&sink),
+            });
+        }
+    }
+
     Ok(devices)
 }
 
-/// 获取可用的模型列表
+/// 获取可用的模型列表 (配置中的模型和模型目录下扫描到的模型)
+///
+/// 扫描结果有缓存，模型目录变化时会发送 `models_changed` 事件
+#[tauri::command]
+async fn get_available_models(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<ModelDescriptor>, AppError> {
+    let config = state.config.lock()?.clone();
+    let models = Arc::clone(&state.models);
+    let models_dir = state.models_dir.clone();
+
+    // 首次扫描需要统计模型文件大小，放到阻塞线程池中执行
+    tauri::async_runtime::spawn_blocking(move || models.models(&config, &models_dir))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// 设置额外扫描的模型根目录 (空字符串表示只扫描应用的模型目录)
 #[tauri::command]
-async fn get_available_models(_state: State<'_, Arc<AppState>>) -> Result<Vec<String>, String> {
-    // TODO: 扫描 models 目录，返回可用的模型列表
-    // 目前只返回默认的 zipformer-small-bilingual
-    Ok(vec!["zipformer-small-bilingual".to_string()])
+async fn set_models_root_dir(
+    state: State<'_, Arc<AppState>>,
+    root_dir: String,
+) -> Result<(), AppError> {
+    if !root_dir.is_empty() && !PathBuf::from(&root_dir).is_dir() {
+        return Err(AppError::NotFound(format!("目录不存在: {}", root_dir)));
+    }
+
+    // 前端每次刷新模型列表都会调用，没有变化时不写配置文件
+    if state.config.lock()?.models_root_dir == root_dir {
+        return Ok(());
+    }
+    state.update_config(|config| {
+        config.models_root_dir = root_dir;
+        Ok(())
+    })?;
+
+    state.refresh_model_roots()
 }
 
 /// 获取模型目录路径
 #[tauri::command]
-async fn get_models_dir(state: State<'_, Arc<AppState>>) -> Result<String, String> {
+async fn get_models_dir(state: State<'_, Arc<AppState>>) -> Result<String, AppError> {
     Ok(state.models_dir.to_string_lossy().to_string())
 }
 
 /// 获取当前配置
 #[tauri::command]
-async fn get_config(state: State<'_, Arc<AppState>>) -> Result<AppConfig, String> {
-    let config = state.config.lock().map_err(|e| e.to_string())?;
+async fn get_config(state: State<'_, Arc<AppState>>) -> Result<AppConfig, AppError> {
+    let config = state.config.lock()?;
     Ok(config.clone())
 }
 
-/// 更新配置
+/// 更新配置并保存到配置文件
 #[tauri::command]
-async fn update_config(state: State<'_, Arc<AppState>>, config: AppConfig) -> Result<(), String> {
-    let mut current_config = state.config.lock().map_err(|e| e.to_string())?;
-    *current_config = config;
+async fn update_config(state: State<'_, Arc<AppState>>, config: AppConfig) -> Result<(), AppError> {
+    let server_config = state.update_config(|current_config| {
+        let server_changed = current_config.server != config.server;
+        *current_config = config;
+        Ok(server_changed.then(|| current_config.server.clone()))
+    })?;
+
+    state.refresh_server_model();
+    state.refresh_hotwords();
+    state.refresh_model_roots()?;
+    if let Some(server_config) = server_config {
+        state.server.apply_config(&server_config).await?;
+    }
     Ok(())
 }
 
+/// 获取字幕推送服务配置
+#[tauri::command]
+async fn get_server_config(state: State<'_, Arc<AppState>>) -> Result<ServerConfig, AppError> {
+    let config = state.config.lock()?;
+    Ok(config.server.clone())
+}
+
+/// 修改字幕推送服务配置，保存后立即按新配置重启服务
+#[tauri::command]
+async fn set_server_config(
+    state: State<'_, Arc<AppState>>,
+    server: ServerConfig,
+) -> Result<ServerStatus, AppError> {
+    state.update_config(|config| {
+        config.server = server.clone();
+        Ok(())
+    })?;
+
+    state.server.apply_config(&server).await?;
+    Ok(state.server.status())
+}
+
+/// 获取字幕推送服务状态
+#[tauri::command]
+async fn get_server_status(state: State<'_, Arc<AppState>>) -> Result<ServerStatus, AppError> {
+    Ok(state.server.status())
+}
+
+/// 获取热词列表
+#[tauri::command]
+async fn get_hotwords(state: State<'_, Arc<AppState>>) -> Result<Vec<Hotword>, AppError> {
+    let config = state.config.lock()?;
+    Ok(config.hotwords.clone())
+}
+
+/// 替换整个热词列表并保存 (识别过程中修改时从下一句开始生效)
+#[tauri::command]
+async fn set_hotwords(
+    state: State<'_, Arc<AppState>>,
+    hotwords: Vec<Hotword>,
+) -> Result<(), AppError> {
+    update_hotwords(&state, |list| {
+        *list = hotwords;
+        Ok(())
+    })
+}
+
+/// 添加热词，已存在时更新加分
+#[tauri::command]
+async fn add_hotword(
+    state: State<'_, Arc<AppState>>,
+    phrase: String,
+    boost: Option<f32>,
+) -> Result<(), AppError> {
+    let hotword = Hotword {
+        phrase: phrase.trim().to_string(),
+        boost,
+    };
+    hotword.validate().map_err(AppError::Config)?;
+
+    update_hotwords(&state, |list| {
+        match list.iter_mut().find(|h| h.phrase == hotword.phrase) {
+            Some(existing) => existing.boost = hotword.boost,
+            None => list.push(hotword),
+        }
+        Ok(())
+    })
+}
+
+/// 删除热词
+#[tauri::command]
+async fn remove_hotword(state: State<'_, Arc<AppState>>, phrase: String) -> Result<(), AppError> {
+    let phrase = phrase.trim();
+    update_hotwords(&state, |list| {
+        let len = list.len();
+        list.retain(|h| h.phrase != phrase);
+        if list.len() == len {
+            return Err(AppError::NotFound(format!("热词不存在: {}", phrase)));
+        }
+        Ok(())
+    })
+}
+
+/// 修改热词列表，校验通过后保存到配置文件
+fn update_hotwords(
+    state: &AppState,
+    update: impl FnOnce(&mut Vec<Hotword>) -> Result<(), AppError>,
+) -> Result<(), AppError> {
+    state.update_config(|config| update(&mut config.hotwords))?;
+    state.refresh_hotwords();
+    Ok(())
+}
+
+/// 获取字幕自动保存配置
+#[tauri::command]
+async fn get_autosave_config(state: State<'_, Arc<AppState>>) -> Result<AutosaveConfig, AppError> {
+    let config = state.config.lock()?;
+    Ok(config.autosave.clone())
+}
+
+/// 修改字幕自动保存配置并保存 (下次开始识别时生效)
+#[tauri::command]
+async fn set_autosave_config(
+    state: State<'_, Arc<AppState>>,
+    autosave: AutosaveConfig,
+) -> Result<(), AppError> {
+    state.update_config(|config| {
+        config.autosave = autosave;
+        Ok(())
+    })
+}
+
 /// 扫描模型文件夹，自动识别模型文件
 #[tauri::command]
-async fn scan_model_dir(dir_path: String) -> Result<ScannedModelFiles, String> {
+async fn scan_model_dir(dir_path: String) -> Result<ScannedModelFiles, AppError> {
     let path = PathBuf::from(&dir_path);
-    ScannedModelFiles::scan_directory(&path).ok_or_else(|| format!("无法扫描目录: {}", dir_path))
+    ScannedModelFiles::scan_directory(&path)
+        .ok_or_else(|| AppError::NotFound(format!("无法扫描目录: {}", dir_path)))
 }
 
 /// 扫描模型根目录，返回所有可用的模型列表
 #[tauri::command]
-async fn scan_models_root_dir(root_dir: String) -> Result<Vec<ScannedModelFiles>, String> {
+async fn scan_models_root_dir(root_dir: String) -> Result<Vec<ScannedModelFiles>, AppError> {
     let root_path = PathBuf::from(&root_dir);
 
     if !root_path.is_dir() {
-        return Err(format!("目录不存在: {}", root_dir));
+        return Err(AppError::NotFound(format!("目录不存在: {}", root_dir)));
     }
 
     let mut models = Vec::new();
@@ -206,11 +472,44 @@ async fn scan_models_root_dir(root_dir: String) -> Result<Vec<ScannedModelFiles>
     Ok(models)
 }
 
+/// 检查模型文件 (默认检查当前模型)，返回每个文件的检查结果
+///
+/// 开始识别时也会做同样的检查，有问题时返回 `model_files_missing` / `model_files_invalid` 错误
+#[tauri::command]
+async fn validate_model(
+    state: State<'_, Arc<AppState>>,
+    model_id: Option<String>,
+) -> Result<ModelReport, AppError> {
+    let config = state.config.lock()?.clone();
+    let model = match &model_id {
+        Some(id) => config
+            .models
+            .iter()
+            .find(|model| &model.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("模型不存在: {}", id)))?,
+        None => config.current_model().ok_or(AppError::ModelNotConfigured)?,
+    };
+    let vad_model = config.resolve_model_path(&config.vad_model, &state.models_dir);
+
+    Ok(model_validation::validate_model(
+        model,
+        &state.models_dir,
+        &vad_model,
+    ))
+}
+
+/// 获取识别是否在进行中 (包括加载和暂停)
+#[tauri::command]
+async fn is_recognition_running(state: State<'_, Arc<AppState>>) -> Result<bool, AppError> {
+    Ok(state.engine.is_active())
+}
+
 /// 获取识别状态
 #[tauri::command]
-async fn is_recognition_running(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
-    let is_running = state.is_running.lock().map_err(|e| e.to_string())?;
-    Ok(*is_running)
+async fn get_recognition_state(
+    state: State<'_, Arc<AppState>>,
+) -> Result<RecognitionState, AppError> {
+    Ok(state.engine.state())
 }
 
 /// 开始识别
@@ -218,25 +517,22 @@ async fn is_recognition_running(state: State<'_, Arc<AppState>>) -> Result<bool,
 async fn start_recognition(
     app_handle: tauri::AppHandle,
     state: State<'_, Arc<AppState>>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     // 检查是否已经在运行
-    {
-        let is_running = state.is_running.lock().map_err(|e| e.to_string())?;
-        if *is_running {
-            return Err("Recognition is already running".to_string());
-        }
+    if state.engine.is_active() {
+        return Err(AppError::InvalidState("识别已在运行".to_string()));
     }
 
     // 获取配置
     let config = {
-        let config = state.config.lock().map_err(|e| e.to_string())?;
+        let config = state.config.lock()?;
         config.clone()
     };
 
     // 获取当前模型配置
     let asr_config = config
         .current_model()
-        .ok_or_else(|| "No ASR model configured".to_string())?
+        .ok_or(AppError::ModelNotConfigured)?
         .clone();
 
     // 打印当前使用的模型信息
@@ -256,16 +552,29 @@ async fn start_recognition(
             println!("  Decoder: {}", decoder);
             println!("  Joiner: {}", joiner);
         }
-        crate::config::AsrModelType::Paraformer { model } => {
+        crate::config::AsrModelType::Paraformer { encoder, decoder } => {
             println!("  Type: Paraformer");
+            println!("  Encoder: {}", encoder);
+            println!("  Decoder: {}", decoder);
+        }
+        crate::config::AsrModelType::Zipformer2Ctc { model, hlg_graph } => {
+            println!("  Type: Zipformer2Ctc");
+            println!("  Model: {}", model);
+            println!("  HLG: {}", hlg_graph.as_deref().unwrap_or("None"));
+        }
+        crate::config::AsrModelType::NemoCtc { model, hlg_graph } => {
+            println!("  Type: NemoCtc");
             println!("  Model: {}", model);
+            println!("  HLG: {}", hlg_graph.as_deref().unwrap_or("None"));
         }
-        crate::config::AsrModelType::Whisper { encoder, decoder } => {
+        crate::config::AsrModelType::Whisper {
+            encoder, decoder, ..
+        } => {
             println!("  Type: Whisper");
             println!("  Encoder: {}", encoder);
             println!("  Decoder: {}", decoder);
         }
-        crate::config::AsrModelType::SenseVoice { model } => {
+        crate::config::AsrModelType::SenseVoice { model, .. } => {
             println!("  Type: SenseVoice");
             println!("  Model: {}", model);
         }
@@ -280,181 +589,283 @@ async fn start_recognition(
     }
     println!("========================================");
 
+    let recognizer_config = RecognizerConfig::from_app_config(&config, &state.models_dir)?;
+
     // 创建音频捕获（根据配置选择捕获模式）
-    #[cfg(target_os = "windows")]
-    use audio_wasapi::CaptureMode;
-
-    #[cfg(target_os = "windows")]
-    let (capture_mode, device_id) = match config.audio_source_type {
-        config::AudioSourceType::SystemAudio => {
-            // 系统音频始终使用默认输出设备
-            (CaptureMode::SystemAudio, None)
-        }
-        config::AudioSourceType::Microphone => {
-            // 麦克风使用用户选择的设备ID
-            let device_id = if config.audio_device_id.is_empty() {
-                None
-            } else {
-                Some(config.audio_device_id.clone())
-            };
-            (CaptureMode::Microphone, device_id)
-        }
+    // 使用用户选择的设备ID（系统音频为输出设备，麦克风为输入设备），未选择时使用默认设备。
+    // 另一类设备的 ID（例如切换音频源前选择的麦克风）视为未选择
+    let (capture_mode, other_prefix) = match config.audio_source_type {
+        config::AudioSourceType::SystemAudio => (CaptureMode::SystemAudio, "input:"),
+        config::AudioSourceType::Microphone => (CaptureMode::Microphone, "output:"),
     };
+    let device_id = Some(config.audio_device_id.clone())
+        .filter(|id| !id.is_empty() && !id.starts_with(other_prefix));
 
-    #[cfg(target_os = "windows")]
-    let mut audio_capture =
-        AudioCapture::new_with_device(asr_config.sample_rate, capture_mode, device_id);
-
-    #[cfg(not(target_os = "windows"))]
-    let mut audio_capture = AudioCapture::new(asr_config.sample_rate);
+    let source = AudioCapture::new(capture_mode, device_id);
+    state
+        .engine
+        .start(recognizer_config, source, event_sink(app_handle))
+}
 
-    let audio_rx = audio_capture
-        .start()
-        .map_err(|e| format!("Failed to start audio capture: {}", e))?;
+/// 识别音频文件 (WAV/FLAC/MP3/OGG)
+///
+/// `real_time` 为 true 时按实际时长播放，否则以识别器允许的最快速度处理
+#[tauri::command]
+async fn start_file_recognition(
+    app_handle: tauri::AppHandle,
+    state: State<'_, Arc<AppState>>,
+    file_path: String,
+    real_time: bool,
+) -> Result<(), AppError> {
+    let recognizer_config = current_recognizer_config(&state)?;
 
-    // 保存音频捕获实例
-    {
-        let mut audio = state.audio_capture.lock().map_err(|e| e.to_string())?;
-        *audio = Some(audio_capture);
-    }
+    println!("Starting file recognition: {}", file_path);
 
-    // 标记为运行中
-    {
-        let mut is_running = state.is_running.lock().map_err(|e| e.to_string())?;
-        *is_running = true;
-    }
+    let pace = if real_time {
+        PlaybackPace::RealTime
+    } else {
+        PlaybackPace::AsFastAsPossible
+    };
 
-    // 在后台线程中运行识别
-    let models_dir = state.models_dir.clone();
-    let state_clone = Arc::clone(&state.inner());
-
-    thread::spawn(move || {
-        // 构建 OnlineRecognizer 配置
-        let (encoder, decoder, joiner) = match &asr_config.model_type {
-            config::AsrModelType::Transducer {
-                encoder,
-                decoder,
-                joiner,
-            } => (
-                models_dir.join(encoder).to_string_lossy().to_string(),
-                models_dir.join(decoder).to_string_lossy().to_string(),
-                models_dir.join(joiner).to_string_lossy().to_string(),
-            ),
-            _ => {
-                eprintln!("OnlineRecognizer only supports Transducer models");
-                let _ =
-                    app_handle.emit("recognition_error", "Only Transducer models are supported");
-                return;
-            }
-        };
+    let source = AudioFileSource::new(file_path, pace);
+    state
+        .engine
+        .start(recognizer_config, source, event_sink(app_handle))
+}
 
-        let online_config = OnlineRecognizerConfig {
-            encoder,
-            decoder,
-            joiner,
-            tokens: models_dir
-                .join(&asr_config.tokens)
-                .to_string_lossy()
-                .to_string(),
-            sample_rate: asr_config.sample_rate as i32,
-            feature_dim: 80,
-            num_threads: asr_config.num_threads,
-            enable_endpoint: true,
-            rule1_min_trailing_silence: 2.4,  // 句子结束静音
-            rule2_min_trailing_silence: 1.2,  // 中间停顿静音
-            rule3_min_utterance_length: 20.0, // 最小语句长度
-            decoding_method: "greedy_search".to_string(),
-            debug: false, // 关闭 debug 模式减少日志输出
-        };
+/// 把识别引擎的事件转发给前端，同时记录字幕并推送给字幕推送服务
+fn event_sink(app_handle: tauri::AppHandle) -> impl FnMut(EngineEvent) + Send + 'static {
+    // 模型加载完成、第一次进入运行状态时开始新的字幕记录 (恢复识别时不重新开始)
+    let mut started = false;
+    // 本会话的编号，在会话的第一个事件 (加载中) 时分配
+    let mut session = None;
+
+    move |event| {
+        let state = app_handle.state::<Arc<AppState>>();
+        if session.is_none() && matches!(event, EngineEvent::State(RecognitionState::Loading)) {
+            session = Some(state.session.fetch_add(1, Ordering::SeqCst) + 1);
+        }
+        // 停止后又开始了新的识别时，旧会话的事件不能清空新会话的记录或结束新会话的自动保存
+        if session != Some(state.session.load(Ordering::SeqCst)) {
+            return;
+        }
 
-        // 通知前端开始加载模型
-        let _ = app_handle.emit("model_loading", serde_json::json!({"loading": true}));
-
-        // 创建 OnlineRecognizer
-        match OnlineRecognizer::new(online_config) {
-            Ok(recognizer) => {
-                // 模型加载完成
-                let _ = app_handle.emit("model_loading", serde_json::json!({"loading": false}));
-                let mut last_text = String::new();
-
-                // 循环处理音频
-                while let Ok(samples) = audio_rx.recv() {
-                    // 检查是否仍在运行
-                    if let Ok(is_running) = state_clone.is_running.lock() {
-                        if !*is_running {
-                            break;
+        match event {
+            EngineEvent::Subtitle(event) => emit_subtitle(&app_handle, &state, &event),
+            EngineEvent::State(next) => {
+                match &next {
+                    RecognitionState::Running if !started => {
+                        started = true;
+                        // 开始新的识别，清空上次的字幕记录
+                        if let Ok(mut transcript) = state.transcript.lock() {
+                            transcript.clear();
                         }
+                        start_autosave(&state);
                     }
-
-                    // 处理音频
-                    let (text, is_endpoint) = recognizer.process(&samples);
-
-                    // 如果有新文本，发送更新
-                    if !text.is_empty() && text != last_text {
-                        // 中间结果，不是最终的
-                        let event = SubtitleEvent::new(text.clone(), false);
-                        let _ = app_handle.emit("subtitle", &event);
-                        last_text = text.clone();
+                    RecognitionState::Idle | RecognitionState::Error(_) => {
+                        // 结束本次自动保存会话 (文件已逐行写入)
+                        if let Ok(mut autosave) = state.autosave.lock() {
+                            *autosave = None;
+                        }
                     }
+                    _ => {}
+                }
+                let _ = app_handle.emit("recognition_state", &next);
+            }
+            EngineEvent::Error(error) => {
+                let _ = app_handle.emit("recognition_error", &*error);
+            }
+            EngineEvent::ModelLoading(loading) => {
+                let _ = app_handle.emit("model_loading", serde_json::json!({ "loading": loading }));
+            }
+            EngineEvent::Finished => {
+                let _ = app_handle.emit("recognition_finished", ());
+            }
+        }
+    }
+}
 
-                    // 如果到达 endpoint，发送最终结果并重置流
-                    if is_endpoint && !last_text.is_empty() {
-                        // 发送最终结果
-                        let event = SubtitleEvent::new(last_text.clone(), true);
-                        let _ = app_handle.emit("subtitle", &event);
-
-                        recognizer.reset();
-                        last_text.clear();
-                    } else if is_endpoint {
-                        // 没有文本但检测到 endpoint，只重置
-                        recognizer.reset();
-                    }
+/// 发送字幕事件，最终结果同时记录到字幕记录中 (启用自动保存时写入文件)
+fn emit_subtitle(app_handle: &tauri::AppHandle, state: &AppState, event: &SubtitleEvent) {
+    if event.is_final {
+        let entry = match state.transcript.lock() {
+            Ok(mut transcript) => {
+                transcript.push(event.start_time, event.end_time, &event.text);
+                transcript.entries().last().cloned()
+            }
+            Err(_) => None,
+        };
+
+        if let (Some(entry), Ok(mut autosave)) = (entry, state.autosave.lock()) {
+            if let Some(writer) = autosave.as_mut() {
+                // 写入失败 (如磁盘已满) 时停止本次自动保存，不影响识别
+                if let Err(e) = writer.append(&entry) {
+                    eprintln!("[Autosave] Failed to write transcript: {}", e);
+                    *autosave = None;
                 }
             }
+        }
+    }
+    let _ = app_handle.emit("subtitle", event);
+    state.server.broadcast("subtitle", event);
+}
+
+/// 启用自动保存时为本次识别创建新会话
+fn start_autosave(state: &AppState) {
+    let config = match state.config.lock() {
+        Ok(config) => config.autosave.clone(),
+        Err(_) => return,
+    };
+
+    let writer = if config.enabled {
+        let dir = match state.autosave_dir() {
+            Ok(dir) => dir,
+            Err(_) => return,
+        };
+        match AutosaveWriter::start(&config, &dir) {
+            Ok(writer) => {
+                println!(
+                    "[Autosave] Saving transcript session {} to {}",
+                    writer.session_id(),
+                    dir.display()
+                );
+                Some(writer)
+            }
             Err(e) => {
-                eprintln!("Failed to create OnlineRecognizer: {}", e);
-                // 模型加载失败，取消加载状态
-                let _ = app_handle.emit("model_loading", serde_json::json!({"loading": false}));
-                let _ = app_handle.emit("recognition_error", &e);
+                eprintln!("[Autosave] Failed to start session: {}", e);
+                None
             }
         }
+    } else {
+        None
+    };
 
-        // 清理状态
-        if let Ok(mut is_running) = state_clone.is_running.lock() {
-            *is_running = false;
-        }
-    });
+    if let Ok(mut autosave) = state.autosave.lock() {
+        *autosave = writer;
+    }
+}
 
-    Ok(())
+/// 列出自动保存的字幕会话 (最新的在前)
+#[tauri::command]
+async fn list_transcript_sessions(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<SessionInfo>, AppError> {
+    let dir = state.autosave_dir()?;
+    autosave::list_sessions(&dir).map_err(|e| AppError::io(&dir, e))
 }
 
-/// 停止识别
+/// 重新载入自动保存的会话，载入后可以再次导出
 #[tauri::command]
-async fn stop_recognition(state: State<'_, Arc<AppState>>) -> Result<(), String> {
-    // 标记为停止
-    {
-        let mut is_running = state.is_running.lock().map_err(|e| e.to_string())?;
-        *is_running = false;
+async fn load_transcript_session(
+    state: State<'_, Arc<AppState>>,
+    session_id: String,
+) -> Result<Vec<TranscriptEntry>, AppError> {
+    if state.engine.is_active() {
+        return Err(AppError::InvalidState(
+            "识别运行时不能加载字幕记录".to_string(),
+        ));
     }
 
-    // 停止音频捕获
-    {
-        let mut audio = state.audio_capture.lock().map_err(|e| e.to_string())?;
-        if let Some(mut capture) = audio.take() {
-            capture.stop();
-        }
+    let dir = state.autosave_dir()?;
+    let entries = autosave::load_session(&dir, &session_id)?;
+
+    let mut transcript = state.transcript.lock()?;
+    transcript.clear();
+    for entry in &entries {
+        transcript.push(entry.start, entry.end, &entry.text);
     }
+    Ok(entries)
+}
 
+/// 获取本次识别的字幕记录
+#[tauri::command]
+async fn get_transcript(state: State<'_, Arc<AppState>>) -> Result<Vec<TranscriptEntry>, AppError> {
+    let transcript = state.transcript.lock()?;
+    Ok(transcript.entries().to_vec())
+}
+
+/// 清空字幕记录
+#[tauri::command]
+async fn clear_transcript(state: State<'_, Arc<AppState>>) -> Result<(), AppError> {
+    let mut transcript = state.transcript.lock()?;
+    transcript.clear();
     Ok(())
 }
 
+/// 导出字幕记录 (SRT/WebVTT/纯文本)
+///
+/// 指定 `path` 时写入文件，总是返回导出的内容。`wrap` 为空时使用默认换行规则
+#[tauri::command]
+async fn export_transcript(
+    state: State<'_, Arc<AppState>>,
+    format: TranscriptFormat,
+    path: Option<String>,
+    wrap: Option<WrapOptions>,
+) -> Result<String, AppError> {
+    let content = {
+        let transcript = state.transcript.lock()?;
+        transcript.export(format, &wrap.unwrap_or_default())
+    };
+
+    if let Some(path) = path {
+        tokio::fs::write(&path, &content)
+            .await
+            .map_err(|e| AppError::io(&path, e))?;
+    }
+
+    Ok(content)
+}
+
+/// 根据当前配置构建识别器配置
+fn current_recognizer_config(state: &AppState) -> Result<RecognizerConfig, AppError> {
+    let config = state.config.lock()?;
+    RecognizerConfig::from_app_config(&config, &state.models_dir)
+}
+
+/// 预加载当前模型 (应用启动时调用，避免第一次开始识别时卡顿)
+#[tauri::command]
+async fn preload_model(
+    app_handle: tauri::AppHandle,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), AppError> {
+    let recognizer_config = current_recognizer_config(&state)?;
+    let engine = state.engine.clone();
+
+    // 加载模型耗时较长，放到阻塞线程池中执行
+    tauri::async_runtime::spawn_blocking(move || {
+        engine.preload(recognizer_config, |loading| {
+            let _ = app_handle.emit("model_loading", serde_json::json!({ "loading": loading }));
+        })
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// 停止识别
+#[tauri::command]
+async fn stop_recognition(state: State<'_, Arc<AppState>>) -> Result<(), AppError> {
+    state.engine.stop()
+}
+
+/// 暂停识别 (保留音频设备和识别器，恢复时无需重新加载)
+#[tauri::command]
+async fn pause_recognition(state: State<'_, Arc<AppState>>) -> Result<(), AppError> {
+    state.engine.pause()
+}
+
+/// 恢复识别
+#[tauri::command]
+async fn resume_recognition(state: State<'_, Arc<AppState>>) -> Result<(), AppError> {
+    state.engine.resume()
+}
+
 /// 打开设置窗口
 #[tauri::command]
-async fn open_settings(app: tauri::AppHandle) -> Result<(), String> {
+async fn open_settings(app: tauri::AppHandle) -> Result<(), AppError> {
     // 检查设置窗口是否已存在
     if let Some(window) = app.get_webview_window("settings") {
-        window.show().map_err(|e| e.to_string())?;
-        window.set_focus().map_err(|e| e.to_string())?;
+        window.show()?;
+        window.set_focus()?;
         return Ok(());
     }
 
@@ -464,15 +875,13 @@ async fn open_settings(app: tauri::AppHandle) -> Result<(), String> {
         .inner_size(600.0, 500.0)
         .resizable(true)
         .center()
-        .build()
-        .map_err(|e| e.to_string())?;
+        .build()?;
 
     Ok(())
 }
 
-/// 获取样式文件路径
-#[tauri::command]
-async fn get_style_path(app: tauri::AppHandle) -> Result<String, String> {
+/// 字幕样式文件路径 (字幕窗口与字幕推送服务的页面共用)
+fn style_path(app: &tauri::AppHandle) -> Result<PathBuf, AppError> {
     let styles_dir = if cfg!(debug_assertions) {
         // 开发环境：使用 src-tauri/resources/styles
         PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -480,32 +889,23 @@ async fn get_style_path(app: tauri::AppHandle) -> Result<String, String> {
             .join("styles")
     } else {
         // 生产环境：使用资源目录
-        app.path()
-            .resource_dir()
-            .map_err(|e| e.to_string())?
-            .join("styles")
+        app.path().resource_dir()?.join("styles")
     };
 
-    let style_path = styles_dir.join("subtitle.css");
-    Ok(style_path.to_string_lossy().to_string())
+    Ok(styles_dir.join("subtitle.css"))
+}
+
+/// 获取样式文件路径
+#[tauri::command]
+async fn get_style_path(app: tauri::AppHandle) -> Result<String, AppError> {
+    Ok(style_path(&app)?.to_string_lossy().to_string())
 }
 
 /// 打开样式编辑器（打开 devtools 并用默认程序打开 CSS 文件）
 #[tauri::command]
-async fn open_style_editor(app: tauri::AppHandle) -> Result<String, String> {
+async fn open_style_editor(app: tauri::AppHandle) -> Result<String, AppError> {
     // 获取样式文件路径
-    let styles_dir = if cfg!(debug_assertions) {
-        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-            .join("resources")
-            .join("styles")
-    } else {
-        app.path()
-            .resource_dir()
-            .map_err(|e| e.to_string())?
-            .join("styles")
-    };
-
-    let style_path = styles_dir.join("subtitle.css");
+    let style_path = style_path(&app)?;
 
     // 打开主窗口的 devtools（需要在 Cargo.toml 中启用 devtools feature）
     if let Some(window) = app.get_webview_window("main") {
@@ -519,21 +919,21 @@ async fn open_style_editor(app: tauri::AppHandle) -> Result<String, String> {
             std::process::Command::new("cmd")
                 .args(["/C", "start", "", &style_path.to_string_lossy()])
                 .spawn()
-                .map_err(|e| format!("无法打开文件: {}", e))?;
+                .map_err(|e| AppError::Internal(format!("无法打开文件: {}", e)))?;
         }
         #[cfg(target_os = "macos")]
         {
             std::process::Command::new("open")
                 .arg(&style_path)
                 .spawn()
-                .map_err(|e| format!("无法打开文件: {}", e))?;
+                .map_err(|e| AppError::Internal(format!("无法打开文件: {}", e)))?;
         }
         #[cfg(target_os = "linux")]
         {
             std::process::Command::new("xdg-open")
                 .arg(&style_path)
                 .spawn()
-                .map_err(|e| format!("无法打开文件: {}", e))?;
+                .map_err(|e| AppError::Internal(format!("无法打开文件: {}", e)))?;
         }
     }
 
@@ -542,10 +942,10 @@ async fn open_style_editor(app: tauri::AppHandle) -> Result<String, String> {
 
 /// 显示主窗口
 #[tauri::command]
-async fn show_main_window(app: tauri::AppHandle) -> Result<(), String> {
+async fn show_main_window(app: tauri::AppHandle) -> Result<(), AppError> {
     if let Some(window) = app.get_webview_window("main") {
-        window.show().map_err(|e| e.to_string())?;
-        window.set_focus().map_err(|e| e.to_string())?;
+        window.show()?;
+        window.set_focus()?;
     }
     Ok(())
 }
@@ -573,9 +973,49 @@ pub fn run() {
                     .join("models")
             };
 
-            // 创建应用状态
-            let state = Arc::new(AppState::new(models_dir));
-            app.manage(state);
+            // 配置文件保存在应用配置目录下
+            let config_path = app
+                .path()
+                .app_config_dir()
+                .expect("Failed to get app config dir")
+                .join(config::CONFIG_FILE_NAME);
+
+            // 字幕自动保存默认放在应用数据目录下
+            let transcripts_dir = app
+                .path()
+                .app_data_dir()
+                .expect("Failed to get app data dir")
+                .join("transcripts");
+
+            // 创建应用状态 (从配置文件加载配置)
+            let state = Arc::new(AppState::new(models_dir, config_path, transcripts_dir));
+            app.manage(Arc::clone(&state));
+
+            // 模型目录有变化时通知前端重新获取模型列表
+            let app_handle = app.handle().clone();
+            if let Err(e) = state.models.watch(move || {
+                let _ = app_handle.emit("models_changed", ());
+            }) {
+                eprintln!("[Models] {}", e);
+            }
+
+            state.refresh_hotwords();
+
+            // 按配置启动字幕推送服务 (默认关闭)
+            state.refresh_server_model();
+            match style_path(app.handle()) {
+                Ok(path) => state.server.set_style_path(path),
+                Err(e) => eprintln!("[Server] Failed to resolve style path: {}", e),
+            }
+            tauri::async_runtime::spawn(async move {
+                let server_config = match state.config.lock() {
+                    Ok(config) => config.server.clone(),
+                    Err(_) => return,
+                };
+                if let Err(e) = state.server.apply_config(&server_config).await {
+                    eprintln!("[Server] {}", e);
+                }
+            });
 
             // 创建托盘菜单
             let show_item = MenuItem::with_id(app, "show", "显示字幕", true, None::<&str>)?;
@@ -667,14 +1107,35 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             enumerate_audio_devices,
             get_available_models,
+            set_models_root_dir,
             get_models_dir,
             get_config,
             update_config,
+            get_hotwords,
+            set_hotwords,
+            add_hotword,
+            remove_hotword,
+            get_autosave_config,
+            set_autosave_config,
+            get_server_config,
+            set_server_config,
+            get_server_status,
             scan_model_dir,
             scan_models_root_dir,
+            validate_model,
             is_recognition_running,
+            get_recognition_state,
             start_recognition,
+            start_file_recognition,
+            preload_model,
             stop_recognition,
+            pause_recognition,
+            resume_recognition,
+            get_transcript,
+            clear_transcript,
+            export_transcript,
+            list_transcript_sessions,
+            load_transcript_session,
             open_settings,
             show_main_window,
             get_style_path,
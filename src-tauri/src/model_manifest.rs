@@ -0,0 +1,416 @@
+//! 模型清单文件 (model.json)
+//!
+//! 模型文件夹中可以放一个 `model.json`，声明模型类型、各版本的文件、语言、采样率和推荐的识别参数。
+//! 扫描模型文件夹时优先读取清单，没有清单时才按文件名识别。例如：
+//!
+//! ```json
+//! {
+//!   "name": "Zipformer 中英双语",
+//!   "variants": [
+//!     { "name": "int8", "type": "Transducer", "encoder": "encoder.int8.onnx",
+//!       "decoder": "decoder.onnx", "joiner": "joiner.int8.onnx" },
+//!     { "name": "fp32", "type": "Transducer", "encoder": "encoder.onnx",
+//!       "decoder": "decoder.onnx", "joiner": "joiner.onnx" }
+//!   ],
+//!   "tokens": "tokens.txt",
+//!   "languages": ["zh", "en"],
+//!   "sample_rate": 16000,
+//!   "feature_dim": 80,
+//!   "options": { "decoding_method": "modified_beam_search", "rule1_min_trailing_silence": 1.8 }
+//! }
+//! ```
+//!
+//! 版本的写法与配置中的 `model_type` 相同，第一个版本为默认版本；文件路径相对于模型文件夹。
+//! `options` 中缺省的字段使用默认值。
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::config::{
+    default_sample_rate, AsrModelType, ModelVariant, RecognizerOptions, ScannedModelFiles,
+};
+
+/// 清单文件名
+pub const MANIFEST_FILE_NAME: &str = "model.json";
+
+/// 模型清单
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModelManifest {
+    /// 显示名称 (缺省使用文件夹名称)
+    #[serde(default)]
+    pub name: Option<String>,
+    /// 模型版本，第一个为默认版本
+    pub variants: Vec<ManifestVariant>,
+    /// tokens 文件
+    #[serde(default = "default_tokens")]
+    pub tokens: String,
+    /// 支持的语言
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// 采样率
+    #[serde(default = "default_sample_rate")]
+    pub sample_rate: u32,
+    /// 特征维度 (优先于 `options.feature_dim`)
+    #[serde(default)]
+    pub feature_dim: Option<i32>,
+    /// 推荐的 endpoint 规则与解码选项
+    #[serde(default)]
+    pub options: Option<RecognizerOptions>,
+}
+
+/// 模型版本
+#[derive(Debug, Clone, Deserialize)]
+pub struct ManifestVariant {
+    /// 版本名称 (如 "int8", "fp32")
+    pub name: String,
+    /// 模型类型与该版本的文件
+    #[serde(flatten)]
+    pub model_type: AsrModelType,
+}
+
+fn default_tokens() -> String {
+    "tokens.txt".to_string()
+}
+
+impl ModelManifest {
+    /// 读取模型文件夹中的清单，没有清单时返回 `Ok(None)`
+    pub fn read(dir: &Path) -> Result<Option<Self>, String> {
+        let path = dir.join(MANIFEST_FILE_NAME);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Self::from_json(&content).map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("无法读取 {}: {}", MANIFEST_FILE_NAME, e)),
+        }
+    }
+
+    /// 解析并检查清单
+    pub fn from_json(content: &str) -> Result<Self, String> {
+        let manifest: Self = serde_json::from_str(content)
+            .map_err(|e| format!("{} 格式错误: {}", MANIFEST_FILE_NAME, e))?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    /// 检查清单内容
+    pub fn validate(&self) -> Result<(), String> {
+        let Some(default) = self.variants.first() else {
+            return Err(format!("{} 至少需要一个模型版本", MANIFEST_FILE_NAME));
+        };
+
+        for (index, variant) in self.variants.iter().enumerate() {
+            if variant.name.trim().is_empty() {
+                return Err("模型版本名称不能为空".to_string());
+            }
+            if self.variants[..index]
+                .iter()
+                .any(|v| v.name == variant.name)
+            {
+                return Err(format!("模型版本名称重复: {}", variant.name));
+            }
+            if variant.model_type.kind() != default.model_type.kind() {
+                return Err(format!(
+                    "模型版本 {} 的类型 {} 与 {} 不一致",
+                    variant.name,
+                    variant.model_type.type_name(),
+                    default.model_type.type_name()
+                ));
+            }
+            if let Some((role, _)) = variant
+                .model_type
+                .files()
+                .iter()
+                .find(|(_, f)| f.is_empty())
+            {
+                return Err(format!("模型版本 {} 缺少 {} 文件", variant.name, role));
+            }
+        }
+
+        if self.tokens.is_empty() {
+            return Err("tokens 文件不能为空".to_string());
+        }
+        if self.sample_rate == 0 {
+            return Err("采样率必须大于 0".to_string());
+        }
+        self.recognizer_options(Path::new("")).validate()
+    }
+
+    /// 推荐的识别参数 (bpe_vocab 转换为基于模型文件夹的路径)
+    pub fn recognizer_options(&self, dir: &Path) -> RecognizerOptions {
+        let mut options = self.options.clone().unwrap_or_default();
+        if let Some(feature_dim) = self.feature_dim {
+            options.feature_dim = feature_dim;
+        }
+        if !options.bpe_vocab.is_empty() {
+            options.bpe_vocab = resolve(dir, &options.bpe_vocab);
+        }
+        options
+    }
+
+    /// 转换为扫描结果，文件路径转换为基于模型文件夹的路径
+    pub fn to_scanned(&self, dir: &Path) -> ScannedModelFiles {
+        let model_name = dir
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        let variants: Vec<(String, AsrModelType)> = self
+            .variants
+            .iter()
+            .map(|v| (v.name.clone(), resolve_model_type(dir, &v.model_type)))
+            .collect();
+        let model_type = variants[0].1.clone();
+        let tokens = resolve(dir, &self.tokens);
+
+        // 只填写存在的文件，缺失的文件通过 `missing_files` 报告
+        let existing = |role: &str| {
+            model_type
+                .files()
+                .into_iter()
+                .find(|(r, file)| *r == role && Path::new(file).is_file())
+                .map(|(_, file)| file.to_string())
+        };
+        let is_complete = model_type
+            .files()
+            .iter()
+            .all(|(_, file)| Path::new(file).is_file())
+            && Path::new(&tokens).is_file();
+
+        ScannedModelFiles {
+            id: model_name.clone(),
+            model_name: self.name.clone().unwrap_or(model_name),
+            model_dir: dir.to_string_lossy().to_string(),
+            model_kind: model_type.kind(),
+            encoder: existing("encoder"),
+            decoder: existing("decoder"),
+            joiner: existing("joiner"),
+            model: existing("model"),
+            hlg_graph: existing("hlg_graph"),
+            tokens: Path::new(&tokens).is_file().then_some(tokens),
+            is_complete,
+            has_multiple_variants: variants.len() > 1,
+            variants: variants
+                .iter()
+                .map(|(name, model_type)| model_variant(name, model_type))
+                .collect(),
+            model_type: Some(model_type),
+            languages: self.languages.clone(),
+            sample_rate: Some(self.sample_rate),
+            options: Some(self.recognizer_options(dir)),
+            manifest_error: None,
+        }
+    }
+}
+
+/// 相对路径转换为基于模型文件夹的路径
+fn resolve(dir: &Path, file: &str) -> String {
+    let path = PathBuf::from(file);
+    if path.is_absolute() {
+        file.to_string()
+    } else {
+        dir.join(path).to_string_lossy().to_string()
+    }
+}
+
+fn resolve_model_type(dir: &Path, model_type: &AsrModelType) -> AsrModelType {
+    let r = |file: &String| resolve(dir, file);
+    match model_type {
+        AsrModelType::Transducer {
+            encoder,
+            decoder,
+            joiner,
+        } => AsrModelType::Transducer {
+            encoder: r(encoder),
+            decoder: r(decoder),
+            joiner: r(joiner),
+        },
+        AsrModelType::Paraformer { encoder, decoder } => AsrModelType::Paraformer {
+            encoder: r(encoder),
+            decoder: r(decoder),
+        },
+        AsrModelType::Zipformer2Ctc { model, hlg_graph } => AsrModelType::Zipformer2Ctc {
+            model: r(model),
+            hlg_graph: hlg_graph.as_ref().map(r),
+        },
+        AsrModelType::NemoCtc { model, hlg_graph } => AsrModelType::NemoCtc {
+            model: r(model),
+            hlg_graph: hlg_graph.as_ref().map(r),
+        },
+        AsrModelType::Whisper {
+            encoder,
+            decoder,
+            language,
+        } => AsrModelType::Whisper {
+            encoder: r(encoder),
+            decoder: r(decoder),
+            language: language.clone(),
+        },
+        AsrModelType::SenseVoice { model, language } => AsrModelType::SenseVoice {
+            model: r(model),
+            language: language.clone(),
+        },
+    }
+}
+
+fn model_variant(name: &str, model_type: &AsrModelType) -> ModelVariant {
+    let files = model_type.files();
+    let file = |role: &str| {
+        files
+            .iter()
+            .find(|(r, _)| *r == role)
+            .map(|(_, file)| file.to_string())
+            .unwrap_or_default()
+    };
+    ModelVariant {
+        variant_name: name.to_string(),
+        encoder: file("encoder"),
+        decoder: file("decoder"),
+        joiner: file("joiner"),
+        model: file("model"),
+        model_type: Some(model_type.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{DecodingMethod, ModelKind};
+    use crate::test_util::TempDir;
+
+    const PARAFORMER: &str = r#"{
+        "name": "Paraformer 中英",
+        "variants": [
+            { "name": "int8", "type": "Paraformer", "encoder": "encoder.int8.onnx", "decoder": "decoder.int8.onnx" },
+            { "name": "fp32", "type": "Paraformer", "encoder": "encoder.onnx", "decoder": "decoder.onnx" }
+        ],
+        "languages": ["zh", "en"],
+        "feature_dim": 80,
+        "options": { "decoding_method": "greedy_search", "rule2_min_trailing_silence": 0.8 }
+    }"#;
+
+    #[test]
+    fn test_scan_prefers_manifest() {
+        let root = TempDir::new("manifest");
+        let dir = root.join("paraformer-zh-en");
+        std::fs::create_dir_all(&dir).unwrap();
+        // 按文件名会被识别为 Transducer
+        for file in [
+            "encoder.int8.onnx",
+            "decoder.int8.onnx",
+            "encoder.onnx",
+            "decoder.onnx",
+            "joiner-unused.onnx",
+            "tokens.txt",
+        ] {
+            std::fs::write(dir.join(file), b"").unwrap();
+        }
+        std::fs::write(dir.join(MANIFEST_FILE_NAME), PARAFORMER).unwrap();
+
+        let scanned = ScannedModelFiles::scan_directory(&dir).unwrap();
+        assert_eq!(scanned.model_kind, ModelKind::Paraformer);
+        assert_eq!(scanned.model_name, "Paraformer 中英");
+        assert_eq!(scanned.id, "paraformer-zh-en");
+        assert!(scanned.is_complete);
+        assert!(scanned.encoder.unwrap().ends_with("encoder.int8.onnx"));
+        assert_eq!(scanned.variants.len(), 2);
+        assert!(scanned.variants[1].decoder.ends_with("decoder.onnx"));
+
+        let model = ScannedModelFiles::scan_directory(&dir)
+            .unwrap()
+            .to_model_config()
+            .unwrap();
+        assert_eq!(model.languages, vec!["zh", "en"]);
+        assert_eq!(model.sample_rate, 16000);
+        assert_eq!(model.options.rule2_min_trailing_silence, 0.8);
+        assert_eq!(model.options.decoding_method, DecodingMethod::GreedySearch);
+        assert!(model.validate().is_ok());
+
+        // 清单中的文件缺失
+        std::fs::remove_file(dir.join("decoder.int8.onnx")).unwrap();
+        let scanned = ScannedModelFiles::scan_directory(&dir).unwrap();
+        assert!(!scanned.is_complete);
+        assert_eq!(scanned.missing_files(), vec!["decoder"]);
+
+        // 清单无效时按文件名识别，并保留错误信息
+        std::fs::write(dir.join(MANIFEST_FILE_NAME), "{ \"variants\": [] }").unwrap();
+        let scanned = ScannedModelFiles::scan_directory(&dir).unwrap();
+        assert_eq!(scanned.model_kind, ModelKind::Transducer);
+        assert!(scanned.model_type.is_none());
+        assert!(scanned.manifest_error.is_some());
+    }
+
+    #[test]
+    fn test_offline_model_manifest() {
+        let manifest = ModelManifest::from_json(
+            r#"{
+                "variants": [{ "name": "int8", "type": "SenseVoice", "model": "model.int8.onnx", "language": "zh" }],
+                "languages": ["zh", "en", "ja", "ko", "yue"]
+            }"#,
+        )
+        .unwrap();
+        let scanned = manifest.to_scanned(Path::new("/models/sense-voice"));
+
+        assert_eq!(scanned.model_kind, ModelKind::SenseVoice);
+        assert!(!scanned.has_multiple_variants);
+        assert_eq!(
+            scanned.model_type,
+            Some(AsrModelType::SenseVoice {
+                model: Path::new("/models/sense-voice")
+                    .join("model.int8.onnx")
+                    .to_string_lossy()
+                    .to_string(),
+                language: "zh".to_string(),
+            })
+        );
+        assert_eq!(scanned.missing_files(), vec!["model", "tokens"]);
+    }
+
+    #[test]
+    fn test_variants_keep_model_type() {
+        let manifest = ModelManifest::from_json(
+            r#"{
+                "variants": [
+                    { "name": "int8", "type": "Zipformer2Ctc", "model": "ctc.int8.onnx" },
+                    { "name": "hlg", "type": "Zipformer2Ctc", "model": "ctc.onnx", "hlg_graph": "HLG.fst" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let dir = Path::new("/models/ctc");
+        let scanned = manifest.to_scanned(dir);
+
+        // 非默认版本的 HLG 解码图不能丢失
+        let resolve = |file: &str| dir.join(file).to_string_lossy().to_string();
+        assert_eq!(scanned.variants[1].model, resolve("ctc.onnx"));
+        assert_eq!(
+            scanned.variants[1].model_type,
+            Some(AsrModelType::Zipformer2Ctc {
+                model: resolve("ctc.onnx"),
+                hlg_graph: Some(resolve("HLG.fst")),
+            })
+        );
+        assert_eq!(scanned.variants[0].model_type, scanned.model_type);
+    }
+
+    #[test]
+    fn test_reject_invalid_manifest() {
+        let invalid = [
+            r#"{ "variants": [] }"#,
+            r#"{ "variants": [{ "name": "a", "type": "Unknown", "model": "m.onnx" }] }"#,
+            r#"{ "variants": [{ "name": "a", "type": "Paraformer", "encoder": "e.onnx", "decoder": "" }] }"#,
+            r#"{ "variants": [
+                { "name": "a", "type": "Paraformer", "encoder": "e.onnx", "decoder": "d.onnx" },
+                { "name": "a", "type": "Paraformer", "encoder": "e2.onnx", "decoder": "d2.onnx" }
+            ] }"#,
+            r#"{ "variants": [
+                { "name": "a", "type": "Paraformer", "encoder": "e.onnx", "decoder": "d.onnx" },
+                { "name": "b", "type": "Zipformer2Ctc", "model": "m.onnx" }
+            ] }"#,
+            r#"{ "variants": [{ "name": "a", "type": "NemoCtc", "model": "m.onnx" }], "feature_dim": 0 }"#,
+        ];
+        for content in invalid {
+            assert!(ModelManifest::from_json(content).is_err(), "{}", content);
+        }
+    }
+}
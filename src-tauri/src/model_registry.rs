@@ -0,0 +1,539 @@
+//! 模型注册表
+//!
+//! 扫描模型根目录 (应用自带的模型目录和用户设置的目录) 下的每个模型文件夹，
+//! 与配置中的模型合并为完整的模型描述。扫描结果缓存在内存中，
+//! 文件系统监视器发现根目录下有变化时清除缓存并通知前端，
+//! 因此新放入的模型文件夹不需要手动扫描就会出现在列表中。
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use notify::event::ModifyKind;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+
+use crate::config::{AppConfig, AsrModelConfig, ModelVariant, ScannedModelFiles};
+use crate::error::AppError;
+
+/// 文件变化后等待的时间，期间的后续变化合并为一次刷新 (复制大模型文件会产生大量事件)
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// 从模型文件夹名称推测语言时识别的语言代码
+const LANGUAGE_CODES: &[&str] = &["zh", "en", "ja", "ko", "yue", "de", "fr", "es", "ru"];
+
+/// 模型描述 (`get_available_models` 返回的列表项)
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelDescriptor {
+    /// 模型 ID (配置中的 ID 或文件夹名称)
+    pub id: String,
+    /// 显示名称
+    pub name: String,
+    /// 模型类型 (与配置中的 `model_type.type` 相同)
+    pub model_type: String,
+    /// 支持的语言
+    pub languages: Vec<String>,
+    /// 模型文件夹
+    pub model_dir: String,
+    /// 可选的模型版本 (int8/fp32)，只有一个版本时为空
+    pub variants: Vec<ModelVariant>,
+    /// 必需的文件是否齐全
+    pub is_complete: bool,
+    /// 缺少的文件的用途
+    pub missing_files: Vec<String>,
+    /// 模型文件占用的磁盘空间 (字节)
+    pub size_bytes: u64,
+    /// 是否已添加到配置中
+    pub configured: bool,
+    /// 是否在模型根目录下扫描到
+    pub scanned: bool,
+    /// 扫描到的模型文件 (前端据此生成配置)，没有扫描到时为 None
+    pub files: Option<ScannedModelFiles>,
+}
+
+/// 扫描到的模型文件夹
+#[derive(Debug, Clone)]
+struct ScannedModel {
+    files: ScannedModelFiles,
+    size_bytes: u64,
+}
+
+/// 模型注册表
+#[derive(Default)]
+pub struct ModelRegistry {
+    /// 扫描的模型根目录
+    roots: Mutex<Vec<PathBuf>>,
+    /// 扫描结果缓存，None 表示需要重新扫描
+    cache: Mutex<Option<Vec<ScannedModel>>>,
+    /// 缓存失效的次数，避免扫描期间发生的变化被旧的扫描结果覆盖
+    generation: AtomicU64,
+    /// 文件系统监视器 (开始监视后才有)
+    watcher: Mutex<Option<RecommendedWatcher>>,
+    /// 模型列表变化时的回调
+    on_change: Mutex<Option<Arc<dyn Fn() + Send + Sync>>>,
+}
+
+impl ModelRegistry {
+    pub fn new(roots: Vec<PathBuf>) -> Self {
+        Self {
+            roots: Mutex::new(dedup_roots(roots)),
+            ..Default::default()
+        }
+    }
+
+    /// 当前扫描的模型根目录
+    pub fn roots(&self) -> Result<Vec<PathBuf>, AppError> {
+        Ok(self.roots.lock()?.clone())
+    }
+
+    /// 修改模型根目录，正在监视时改为监视新的目录
+    ///
+    /// 返回目录是否有变化
+    pub fn set_roots(self: &Arc<Self>, roots: Vec<PathBuf>) -> Result<bool, AppError> {
+        let roots = dedup_roots(roots);
+        {
+            let mut current = self.roots.lock()?;
+            if *current == roots {
+                return Ok(false);
+            }
+            *current = roots;
+        }
+
+        self.invalidate();
+        let on_change = self.on_change.lock()?.clone();
+        if let Some(on_change) = on_change {
+            self.start_watcher(on_change)?;
+        }
+        Ok(true)
+    }
+
+    /// 清除扫描结果缓存，下次获取模型列表时重新扫描
+    pub fn invalidate(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        if let Ok(mut cache) = self.cache.lock() {
+            *cache = None;
+        }
+    }
+
+    /// 合并扫描结果与配置中的模型
+    ///
+    /// 配置中的模型在前 (保持配置中的顺序)，文件夹与配置中的模型相同时合并为一项；
+    /// 其余扫描到的模型按名称排序。配置中的相对路径基于 `models_dir`
+    pub fn models(
+        &self,
+        config: &AppConfig,
+        models_dir: &Path,
+    ) -> Result<Vec<ModelDescriptor>, AppError> {
+        let mut scanned = self.scanned()?;
+        let mut models = Vec::new();
+
+        for model in &config.models {
+            let model_dir = if model.model_dir.is_empty() {
+                None
+            } else {
+                Some(config.resolve_model_path(&model.model_dir, models_dir))
+            };
+            let matched = scanned.iter().position(|s| {
+                s.files.id == model.id
+                    || model_dir
+                        .as_deref()
+                        .is_some_and(|dir| Path::new(&s.files.model_dir) == dir)
+            });
+            let matched = matched.map(|index| scanned.remove(index));
+            models.push(configured_descriptor(
+                model,
+                model_dir.as_deref(),
+                matched,
+                models_dir,
+            ));
+        }
+
+        models.extend(scanned.into_iter().map(scanned_descriptor));
+        Ok(models)
+    }
+
+    /// 扫描结果 (有缓存时直接返回缓存)
+    fn scanned(&self) -> Result<Vec<ScannedModel>, AppError> {
+        if let Some(cache) = self.cache.lock()?.as_ref() {
+            return Ok(cache.clone());
+        }
+
+        let generation = self.generation.load(Ordering::SeqCst);
+        let models = scan_roots(&self.roots()?);
+
+        let mut cache = self.cache.lock()?;
+        if self.generation.load(Ordering::SeqCst) == generation {
+            *cache = Some(models.clone());
+        }
+        Ok(models)
+    }
+
+    /// 开始监视模型根目录，模型文件夹或其中的文件有变化时清除缓存并调用 `on_change`
+    ///
+    /// 不存在的根目录不会被监视
+    pub fn watch(
+        self: &Arc<Self>,
+        on_change: impl Fn() + Send + Sync + 'static,
+    ) -> Result<(), AppError> {
+        let on_change: Arc<dyn Fn() + Send + Sync> = Arc::new(on_change);
+        *self.on_change.lock()? = Some(Arc::clone(&on_change));
+        self.start_watcher(on_change)
+    }
+
+    fn start_watcher(
+        self: &Arc<Self>,
+        on_change: Arc<dyn Fn() + Send + Sync>,
+    ) -> Result<(), AppError> {
+        // 先停止旧的监视器，旧的刷新线程随之退出
+        *self.watcher.lock()? = None;
+
+        let (tx, rx) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+                Ok(event) if changes_models(&event.kind) => {
+                    let _ = tx.send(());
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("[Models] Watch error: {}", e),
+            })
+            .map_err(|e| AppError::Internal(format!("无法监视模型目录: {}", e)))?;
+
+        for root in self.roots()? {
+            if !root.is_dir() {
+                continue;
+            }
+            match watcher.watch(&root, RecursiveMode::Recursive) {
+                Ok(()) => eprintln!("[Models] Watching {}", root.display()),
+                Err(e) => eprintln!("[Models] Failed to watch {}: {}", root.display(), e),
+            }
+        }
+
+        let registry = Arc::downgrade(self);
+        std::thread::spawn(move || refresh_loop(rx, registry, on_change));
+
+        *self.watcher.lock()? = Some(watcher);
+        Ok(())
+    }
+}
+
+/// 等待文件变化，合并短时间内的连续变化后清除缓存并通知
+///
+/// 监视器被替换或注册表被释放后 (发送端关闭) 退出
+fn refresh_loop(
+    rx: mpsc::Receiver<()>,
+    registry: Weak<ModelRegistry>,
+    on_change: Arc<dyn Fn() + Send + Sync>,
+) {
+    while rx.recv().is_ok() {
+        loop {
+            match rx.recv_timeout(DEBOUNCE) {
+                Ok(()) => continue,
+                Err(mpsc::RecvTimeoutError::Timeout) => break,
+                Err(mpsc::RecvTimeoutError::Disconnected) => return,
+            }
+        }
+
+        let Some(registry) = registry.upgrade() else {
+            return;
+        };
+        eprintln!("[Models] Model directory changed, refreshing");
+        registry.invalidate();
+        on_change();
+    }
+}
+
+/// 是否是会影响模型列表的变化 (忽略读取文件和修改时间等属性)
+fn changes_models(kind: &EventKind) -> bool {
+    !matches!(
+        kind,
+        EventKind::Access(_) | EventKind::Modify(ModifyKind::Metadata(_))
+    )
+}
+
+/// 去掉重复和空的根目录，保持原有顺序
+fn dedup_roots(roots: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut unique: Vec<PathBuf> = Vec::new();
+    for root in roots {
+        if !root.as_os_str().is_empty() && !unique.contains(&root) {
+            unique.push(root);
+        }
+    }
+    unique
+}
+
+/// 扫描每个根目录下的子目录，ID 重复时保留先扫描到的 (应用的模型目录优先)
+fn scan_roots(roots: &[PathBuf]) -> Vec<ScannedModel> {
+    let mut models: Vec<ScannedModel> = Vec::new();
+
+    for root in roots {
+        let Ok(entries) = std::fs::read_dir(root) else {
+            continue;
+        };
+        let mut found: Vec<ScannedModel> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .filter_map(|path| {
+                let files = ScannedModelFiles::scan_directory(&path)?;
+                Some(ScannedModel {
+                    files,
+                    size_bytes: dir_size(&path),
+                })
+            })
+            .collect();
+        found.sort_by(|a, b| a.files.model_name.cmp(&b.files.model_name));
+
+        for model in found {
+            if models.iter().any(|m| m.files.id == model.files.id) {
+                eprintln!(
+                    "[Models] Duplicate model id {}, ignoring {}",
+                    model.files.id, model.files.model_dir
+                );
+                continue;
+            }
+            models.push(model);
+        }
+    }
+
+    models
+}
+
+/// 配置中的模型，`scanned` 为同一文件夹的扫描结果
+fn configured_descriptor(
+    model: &AsrModelConfig,
+    model_dir: Option<&Path>,
+    scanned: Option<ScannedModel>,
+    models_dir: &Path,
+) -> ModelDescriptor {
+    let missing_files = model.missing_files(models_dir);
+    let size_bytes = match (&scanned, model_dir) {
+        (Some(scanned), _) => scanned.size_bytes,
+        (None, Some(dir)) if dir.is_dir() => dir_size(dir),
+        // 没有模型文件夹时只统计配置的文件
+        _ => model
+            .files()
+            .into_iter()
+            .filter(|(_, file)| !file.is_empty())
+            .filter_map(|(_, file)| std::fs::metadata(models_dir.join(file)).ok())
+            .map(|metadata| metadata.len())
+            .sum(),
+    };
+
+    ModelDescriptor {
+        id: model.id.clone(),
+        name: model.name.clone(),
+        model_type: model.model_type.type_name().to_string(),
+        languages: model.languages.clone(),
+        model_dir: model_dir
+            .map(|dir| dir.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        variants: scanned
+            .as_ref()
+            .map(|s| s.files.variants.clone())
+            .unwrap_or_default(),
+        is_complete: missing_files.is_empty(),
+        missing_files,
+        size_bytes,
+        configured: true,
+        scanned: scanned.is_some(),
+        files: scanned.map(|s| s.files),
+    }
+}
+
+/// 只在模型根目录下扫描到的模型
+fn scanned_descriptor(model: ScannedModel) -> ModelDescriptor {
+    let ScannedModel { files, size_bytes } = model;
+    ModelDescriptor {
+        languages: if files.languages.is_empty() {
+            guess_languages(&files.model_name)
+        } else {
+            files.languages.clone()
+        },
+        model_type: files.model_kind.type_name().to_string(),
+        missing_files: files.missing_files(),
+        is_complete: files.is_complete,
+        id: files.id.clone(),
+        name: files.model_name.clone(),
+        model_dir: files.model_dir.clone(),
+        variants: files.variants.clone(),
+        size_bytes,
+        configured: false,
+        scanned: true,
+        files: Some(files),
+    }
+}
+
+/// 从文件夹名称推测语言 (没有 model.json 时使用，sherpa-onnx 的模型名称中通常包含语言代码，如 bilingual-zh-en)
+fn guess_languages(name: &str) -> Vec<String> {
+    let mut languages = Vec::new();
+    for part in name.to_lowercase().split(['-', '_', '.']) {
+        if LANGUAGE_CODES.contains(&part) && !languages.iter().any(|l| l == part) {
+            languages.push(part.to_string());
+        }
+    }
+    languages
+}
+
+/// 文件夹中所有文件的大小之和 (不跟随符号链接)
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let metadata = std::fs::symlink_metadata(entry.path()).ok()?;
+            if metadata.is_dir() {
+                Some(dir_size(&entry.path()))
+            } else if metadata.is_file() {
+                Some(metadata.len())
+            } else {
+                None
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AsrModelType;
+    use crate::test_util::TempDir;
+
+    fn create_model(root: &Path, name: &str, files: &[(&str, usize)]) -> PathBuf {
+        let dir = root.join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (file, size) in files {
+            std::fs::write(dir.join(file), vec![0u8; *size]).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_scan_and_merge_with_config() {
+        let root = TempDir::new("registry");
+        let extra = TempDir::new("registry-extra");
+
+        let configured_dir = create_model(
+            &root,
+            "zipformer-bilingual-zh-en",
+            &[
+                ("encoder.onnx", 100),
+                ("decoder.onnx", 20),
+                ("joiner.onnx", 10),
+                ("tokens.txt", 5),
+            ],
+        );
+        create_model(
+            &extra,
+            "paraformer-zh",
+            &[("encoder.onnx", 50), ("decoder.onnx", 50)],
+        );
+        // 与应用模型目录中的模型重名，忽略
+        create_model(&extra, "zipformer-bilingual-zh-en", &[("model.onnx", 1)]);
+
+        let mut config = AppConfig::default();
+        let model = &mut config.models[0];
+        model.id = "my-model".to_string();
+        model.model_dir = configured_dir.to_string_lossy().to_string();
+        model.model_type = AsrModelType::Transducer {
+            encoder: configured_dir
+                .join("encoder.onnx")
+                .to_string_lossy()
+                .to_string(),
+            decoder: configured_dir
+                .join("decoder.onnx")
+                .to_string_lossy()
+                .to_string(),
+            joiner: configured_dir
+                .join("joiner.onnx")
+                .to_string_lossy()
+                .to_string(),
+        };
+        model.tokens = configured_dir
+            .join("tokens.txt")
+            .to_string_lossy()
+            .to_string();
+
+        let registry = ModelRegistry::new(vec![
+            root.to_path_buf(),
+            extra.to_path_buf(),
+            root.to_path_buf(),
+        ]);
+        assert_eq!(
+            registry.roots().unwrap(),
+            vec![root.to_path_buf(), extra.to_path_buf()]
+        );
+
+        let models = registry.models(&config, &root).unwrap();
+        assert_eq!(models.len(), 2);
+
+        let configured = &models[0];
+        assert_eq!(configured.id, "my-model");
+        assert_eq!(configured.model_type, "Transducer");
+        assert!(configured.configured && configured.scanned);
+        assert!(configured.is_complete);
+        assert_eq!(configured.size_bytes, 135);
+        let files = configured.files.as_ref().unwrap();
+        assert_eq!(files.id, "zipformer-bilingual-zh-en");
+        assert!(files.joiner.is_some());
+
+        let scanned = &models[1];
+        assert_eq!(scanned.id, "paraformer-zh");
+        assert_eq!(scanned.model_type, "Paraformer");
+        assert_eq!(scanned.languages, vec!["zh"]);
+        assert!(!scanned.configured && scanned.scanned);
+        assert!(!scanned.is_complete);
+        assert_eq!(scanned.missing_files, vec!["tokens"]);
+        assert_eq!(scanned.size_bytes, 100);
+        assert!(scanned.files.as_ref().unwrap().decoder.is_some());
+
+        // 缓存失效前不会看到新的文件
+        std::fs::write(extra.join("paraformer-zh").join("tokens.txt"), b"a 0\n").unwrap();
+        assert!(!registry.models(&config, &root).unwrap()[1].is_complete);
+        registry.invalidate();
+        assert!(registry.models(&config, &root).unwrap()[1].is_complete);
+    }
+
+    #[test]
+    fn test_configured_model_without_folder() {
+        let root = TempDir::new("registry-config");
+        std::fs::write(root.join("encoder.onnx"), [0u8; 8]).unwrap();
+        std::fs::write(root.join("tokens.txt"), [0u8; 2]).unwrap();
+
+        // 默认配置的文件是相对于模型目录的路径
+        let config = AppConfig::default();
+        let models = ModelRegistry::new(Vec::new())
+            .models(&config, &root)
+            .unwrap();
+
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].id, "default");
+        assert!(!models[0].scanned);
+        assert!(models[0].files.is_none());
+        assert_eq!(models[0].missing_files, vec!["decoder", "joiner"]);
+        assert_eq!(models[0].size_bytes, 10);
+    }
+
+    #[test]
+    fn test_guess_languages() {
+        assert_eq!(
+            guess_languages("sherpa-onnx-streaming-zipformer-bilingual-zh-en-2023-02-20"),
+            vec!["zh", "en"]
+        );
+        assert!(guess_languages("my_model").is_empty());
+    }
+
+    #[test]
+    fn test_ignore_access_events() {
+        use notify::event::{AccessKind, CreateKind, MetadataKind};
+
+        assert!(!changes_models(&EventKind::Access(AccessKind::Any)));
+        assert!(!changes_models(&EventKind::Modify(ModifyKind::Metadata(
+            MetadataKind::AccessTime
+        ))));
+        assert!(changes_models(&EventKind::Create(CreateKind::Folder)));
+    }
+}
@@ -0,0 +1,432 @@
+//! 模型文件检查
+//!
+//! sherpa-onnx 加载失败时只返回空指针，无法知道是哪个文件有问题。
+//! 加载识别器之前先检查模型配置引用的每个文件：是否存在、能否读取、tokens.txt 的格式，
+//! 以及 ONNX 元数据与配置是否一致 (流式/离线、模型类型、特征维度、采样率)。
+
+use crate::config::AsrModelConfig;
+use crate::error::AppError;
+use crate::onnx::OnnxModelInfo;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// 表示流式模型的元数据 (sherpa-onnx 导出的流式 Zipformer/Conformer/NeMo 模型)
+const STREAMING_KEYS: &[&str] = &[
+    "decode_chunk_len",
+    "chunk_size",
+    "left_context",
+    "cache_last_channel_dim1",
+];
+
+/// 特征维度的元数据
+const FEATURE_DIM_KEYS: &[&str] = &["feat_dim", "feature_dim"];
+
+/// 一个模型的检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelReport {
+    pub model_id: String,
+    pub model_name: String,
+    /// 相对路径的基准目录
+    pub model_dir: PathBuf,
+    pub files: Vec<FileReport>,
+    /// 所有文件都可用
+    pub valid: bool,
+}
+
+/// 一个文件的检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct FileReport {
+    /// 文件用途 (encoder/decoder/joiner/model/hlg_graph/tokens/bpe_vocab/vad_model)
+    pub role: String,
+    pub path: PathBuf,
+    /// 文件大小 (字节)，文件不存在时为 None
+    pub size: Option<u64>,
+    /// ONNX 模型的自定义元数据
+    pub metadata: BTreeMap<String, String>,
+    /// tokens 文件中的 token 数
+    pub token_count: Option<usize>,
+    /// None 表示文件可用
+    pub problem: Option<FileProblem>,
+}
+
+/// 文件的问题
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+pub enum FileProblem {
+    /// 配置中没有指定文件
+    NotConfigured,
+    /// 文件不存在
+    Missing,
+    /// 文件无法读取 (权限不足、是目录或为空文件)
+    Unreadable(String),
+    /// 文件格式错误
+    InvalidFormat(String),
+    /// 与模型配置不一致
+    Mismatch(String),
+}
+
+impl fmt::Display for FileProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotConfigured => write!(f, "未指定文件"),
+            Self::Missing => write!(f, "文件不存在"),
+            Self::Unreadable(message) => write!(f, "无法读取: {}", message),
+            Self::InvalidFormat(message) => write!(f, "格式错误: {}", message),
+            Self::Mismatch(message) => write!(f, "与配置不一致: {}", message),
+        }
+    }
+}
+
+impl ModelReport {
+    /// 有问题时返回错误：缺少文件时为 `ModelFilesMissing`，否则为 `ModelFilesInvalid`
+    pub fn check(&self) -> Result<(), AppError> {
+        let missing: Vec<String> = self
+            .files
+            .iter()
+            .filter(|file| {
+                matches!(
+                    file.problem,
+                    Some(FileProblem::NotConfigured | FileProblem::Missing)
+                )
+            })
+            .map(|file| file.role.clone())
+            .collect();
+        if !missing.is_empty() {
+            return Err(AppError::ModelFilesMissing {
+                model: self.model_name.clone(),
+                model_dir: self.model_dir.clone(),
+                files: missing,
+            });
+        }
+
+        let problems: Vec<(String, String)> = self
+            .files
+            .iter()
+            .filter_map(|file| Some((file.role.clone(), file.problem.as_ref()?.to_string())))
+            .collect();
+        if !problems.is_empty() {
+            return Err(AppError::ModelFilesInvalid {
+                model: self.model_name.clone(),
+                problems,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// 检查模型配置引用的所有文件 (相对路径基于 `models_dir`)
+///
+/// 离线模型同时检查 VAD 模型
+pub fn validate_model(
+    asr_config: &AsrModelConfig,
+    models_dir: &Path,
+    vad_model: &Path,
+) -> ModelReport {
+    let mut files: Vec<FileReport> = asr_config
+        .files()
+        .into_iter()
+        .map(|(role, file)| check_file(role, file, models_dir, asr_config))
+        .collect();
+    if asr_config.model_type.is_offline() {
+        let vad_model = vad_model.to_string_lossy();
+        files.push(check_file("vad_model", &vad_model, models_dir, asr_config));
+    }
+
+    // 模型可能输出 tokens 文件中没有的 id
+    let vocab_size = files
+        .iter()
+        .filter(|file| file.role != "vad_model")
+        .filter_map(|file| file.metadata.get("vocab_size")?.parse::<usize>().ok())
+        .max();
+    if let Some(vocab_size) = vocab_size {
+        for file in files.iter_mut().filter(|file| file.problem.is_none()) {
+            if let Some(count) = file.token_count.filter(|&count| count < vocab_size) {
+                file.problem = Some(FileProblem::Mismatch(format!(
+                    "只有 {} 个 token，模型的词表大小为 {}",
+                    count, vocab_size
+                )));
+            }
+        }
+    }
+
+    ModelReport {
+        model_id: asr_config.id.clone(),
+        model_name: asr_config.name.clone(),
+        model_dir: models_dir.to_path_buf(),
+        valid: files.iter().all(|file| file.problem.is_none()),
+        files,
+    }
+}
+
+fn check_file(
+    role: &str,
+    file: &str,
+    models_dir: &Path,
+    asr_config: &AsrModelConfig,
+) -> FileReport {
+    let path = models_dir.join(file);
+    let mut report = FileReport {
+        role: role.to_string(),
+        path: path.clone(),
+        size: None,
+        metadata: BTreeMap::new(),
+        token_count: None,
+        problem: None,
+    };
+
+    if file.is_empty() {
+        report.problem = Some(FileProblem::NotConfigured);
+        return report;
+    }
+    if !path.exists() {
+        report.problem = Some(FileProblem::Missing);
+        return report;
+    }
+    report.size = path.metadata().ok().map(|metadata| metadata.len());
+
+    let result = check_readable(&path).and_then(|()| {
+        if role == "tokens" {
+            report.token_count = Some(parse_tokens(&path).map_err(FileProblem::InvalidFormat)?);
+        } else if is_onnx(&path) {
+            let info = OnnxModelInfo::read(&path)
+                .map_err(|e| FileProblem::InvalidFormat(e.to_string()))?;
+            report.metadata = info.metadata.clone();
+            if role != "vad_model" {
+                check_metadata(&info, asr_config).map_err(FileProblem::Mismatch)?;
+            }
+        }
+        Ok(())
+    });
+    report.problem = result.err();
+    report
+}
+
+/// 能打开并读取至少一个字节
+fn check_readable(path: &Path) -> Result<(), FileProblem> {
+    if path.is_dir() {
+        return Err(FileProblem::Unreadable("是目录而不是文件".to_string()));
+    }
+    let mut file = File::open(path).map_err(|e| FileProblem::Unreadable(e.to_string()))?;
+    let mut byte = [0u8];
+    match file.read(&mut byte) {
+        Ok(0) => Err(FileProblem::Unreadable("文件为空".to_string())),
+        Ok(_) => Ok(()),
+        Err(e) => Err(FileProblem::Unreadable(e.to_string())),
+    }
+}
+
+fn is_onnx(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("onnx"))
+}
+
+/// 解析 tokens.txt，返回 token 数
+///
+/// 每行为 `<token> <id>`，id 为从 0 开始不重复的整数 (token 本身可以是空格)
+pub fn parse_tokens(path: &Path) -> Result<usize, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut ids = std::collections::HashSet::new();
+
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("第 {} 行: {}", index + 1, e))?;
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            continue;
+        }
+        let id = line
+            .rsplit_once(|c: char| c.is_whitespace())
+            .and_then(|(_, id)| id.parse::<u32>().ok())
+            .ok_or_else(|| format!("第 {} 行不是 \"<token> <id>\" 格式: {}", index + 1, line))?;
+        if !ids.insert(id) {
+            return Err(format!("第 {} 行的 id {} 重复", index + 1, id));
+        }
+    }
+
+    if ids.is_empty() {
+        return Err("没有任何 token".to_string());
+    }
+    Ok(ids.len())
+}
+
+/// 从元数据判断是否是流式模型，无法判断时返回 None
+pub(crate) fn is_streaming(info: &OnnxModelInfo) -> Option<bool> {
+    if let Some(streaming) = info.metadata_int("is_streaming") {
+        return Some(streaming != 0);
+    }
+    if offline_family(info).is_some() {
+        return Some(false);
+    }
+    STREAMING_KEYS
+        .iter()
+        .any(|key| info.metadata.contains_key(*key))
+        .then_some(true)
+}
+
+/// 元数据中的离线模型类型 (Whisper/SenseVoice)
+fn offline_family(info: &OnnxModelInfo) -> Option<&'static str> {
+    let model_type = info.model_type()?;
+    if model_type.contains("whisper") {
+        Some("Whisper")
+    } else if model_type.contains("sense_voice") || model_type.contains("sensevoice") {
+        Some("SenseVoice")
+    } else {
+        None
+    }
+}
+
+/// 检查元数据与模型配置是否一致
+fn check_metadata(info: &OnnxModelInfo, asr_config: &AsrModelConfig) -> Result<(), String> {
+    let configured = asr_config.model_type.type_name();
+
+    if let Some(family) = offline_family(info) {
+        if family != configured {
+            return Err(format!("这是 {} 模型，配置为 {}", family, configured));
+        }
+    }
+    match is_streaming(info) {
+        Some(true) if asr_config.model_type.is_offline() => {
+            return Err(format!("这是流式模型，配置为离线的 {}", configured));
+        }
+        Some(false) if !asr_config.model_type.is_offline() => {
+            return Err(format!("这是离线模型，配置为流式的 {}", configured));
+        }
+        _ => {}
+    }
+
+    let feature_dim = FEATURE_DIM_KEYS
+        .iter()
+        .find_map(|key| info.metadata_int(key));
+    if let Some(feature_dim) = feature_dim {
+        if feature_dim != i64::from(asr_config.options.feature_dim) {
+            return Err(format!(
+                "模型的特征维度为 {}，配置为 {}",
+                feature_dim, asr_config.options.feature_dim
+            ));
+        }
+    }
+
+    if let Some(sample_rate) = info.metadata_int("sample_rate") {
+        if sample_rate != i64::from(asr_config.sample_rate) {
+            return Err(format!(
+                "模型的采样率为 {}，配置为 {}",
+                sample_rate, asr_config.sample_rate
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AppConfig, AsrModelType};
+    use crate::onnx::tests::fake_model;
+    use crate::test_util::TempDir;
+
+    fn transducer(dir: &Path) -> AsrModelConfig {
+        let mut model = AppConfig::default().models.remove(0);
+        model.name = "zipformer".to_string();
+        model.model_type = AsrModelType::Transducer {
+            encoder: "encoder.onnx".to_string(),
+            decoder: "decoder.onnx".to_string(),
+            joiner: "joiner.onnx".to_string(),
+        };
+        model.tokens = "tokens.txt".to_string();
+        model.model_dir = dir.to_string_lossy().to_string();
+        model
+    }
+
+    fn problem<'a>(report: &'a ModelReport, role: &str) -> Option<&'a FileProblem> {
+        report
+            .files
+            .iter()
+            .find(|file| file.role == role)
+            .and_then(|file| file.problem.as_ref())
+    }
+
+    #[test]
+    fn test_validate_streaming_transducer() {
+        let dir = TempDir::new("validate");
+
+        let write = |file: &str, data: &[u8]| std::fs::write(dir.join(file), data).unwrap();
+        write(
+            "encoder.onnx",
+            &fake_model(&[("model_type", "zipformer2"), ("decode_chunk_len", "32")]),
+        );
+        write("decoder.onnx", &fake_model(&[("vocab_size", "3")]));
+        write("tokens.txt", "<blk> 0\n<sos/eos> 1\n  2\n".as_bytes());
+
+        let model = transducer(&dir);
+        let report = validate_model(&model, &dir, Path::new("silero_vad.onnx"));
+        assert!(!report.valid);
+        assert_eq!(problem(&report, "joiner"), Some(&FileProblem::Missing));
+        assert_eq!(report.files[3].token_count, Some(3));
+        assert!(matches!(
+            report.check(),
+            Err(AppError::ModelFilesMissing { files, .. }) if files == ["joiner"]
+        ));
+
+        // 误把 tokens.txt 当作 joiner
+        write("joiner.onnx", "<blk> 0\n".as_bytes());
+        let report = validate_model(&model, &dir, Path::new("silero_vad.onnx"));
+        assert!(matches!(
+            problem(&report, "joiner"),
+            Some(FileProblem::InvalidFormat(_))
+        ));
+        assert!(matches!(
+            report.check(),
+            Err(AppError::ModelFilesInvalid { problems, .. }) if problems[0].0 == "joiner"
+        ));
+
+        write("joiner.onnx", &fake_model(&[]));
+        let report = validate_model(&model, &dir, Path::new("silero_vad.onnx"));
+        assert!(report.valid, "{:?}", report);
+        assert!(report.check().is_ok());
+
+        // 词表大于 tokens 文件
+        write("decoder.onnx", &fake_model(&[("vocab_size", "500")]));
+        let report = validate_model(&model, &dir, Path::new("silero_vad.onnx"));
+        assert!(matches!(
+            problem(&report, "tokens"),
+            Some(FileProblem::Mismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_metadata_mismatch() {
+        let model = transducer(Path::new("."));
+        let check = |metadata: &[(&str, &str)]| {
+            let data = fake_model(metadata);
+            let len = data.len() as u64;
+            let info = OnnxModelInfo::parse(std::io::Cursor::new(data), len).unwrap();
+            check_metadata(&info, &model)
+        };
+
+        assert!(check(&[("decode_chunk_len", "32"), ("feat_dim", "80")]).is_ok());
+        // 信息不足时不报错
+        assert!(check(&[]).is_ok());
+        assert!(check(&[("model_type", "whisper-tiny.en")]).is_err());
+        assert!(check(&[("is_streaming", "0")]).is_err());
+        assert!(check(&[("feat_dim", "128")]).is_err());
+        assert!(check(&[("sample_rate", "8000")]).is_err());
+    }
+
+    #[test]
+    fn test_parse_tokens() {
+        let dir = TempDir::new("tokens");
+        let parse = |content: &str| {
+            let path = dir.join("tokens.txt");
+            std::fs::write(&path, content).unwrap();
+            parse_tokens(&path)
+        };
+
+        assert_eq!(parse("<blk> 0\r\n▁THE 1\r\n\r\n"), Ok(2));
+        assert!(parse("").is_err());
+        assert!(parse("<blk> 0\n<unk>\n").unwrap_err().contains("第 2 行"));
+        assert!(parse("a 0\nb 0\n").unwrap_err().contains("重复"));
+    }
+}
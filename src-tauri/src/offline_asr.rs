@@ -0,0 +1,323 @@
+//! OfflineRecognizer (非流式识别器) 封装
+//!
+//! 与 online_asr 一样直接使用 sherpa_rs_sys FFI 绑定。
+//! Whisper / SenseVoice 等离线模型只能整段识别，因此先用 Silero VAD
+//! 把音频切分成语音段，每段结束后再交给离线模型解码。
+
+use crate::online_asr::RecognitionResult;
+use sherpa_rs::sherpa_rs_sys;
+use std::ffi::CString;
+
+/// 离线模型文件
+#[derive(Debug, Clone, PartialEq)]
+pub enum OfflineModel {
+    /// Whisper 模型 (encoder/decoder)
+    Whisper {
+        encoder: String,
+        decoder: String,
+        /// 语言代码，空字符串表示自动检测
+        language: String,
+    },
+    /// SenseVoice 模型 (单一模型文件)
+    SenseVoice {
+        model: String,
+        /// 语言代码，空字符串表示自动检测
+        language: String,
+    },
+}
+
+/// 一个语音段的识别结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeechSegment {
+    /// 语音段开始时间 (秒，相对识别开始)
+    pub start: f32,
+    /// 语音段时长 (秒)
+    pub duration: f32,
+    /// 识别结果 (token 时间相对语音段开始)
+    pub result: RecognitionResult,
+}
+
+/// OfflineRecognizer 配置
+#[derive(Debug, Clone, PartialEq)]
+pub struct OfflineRecognizerConfig {
+    /// 模型文件
+    pub model: OfflineModel,
+    /// tokens 文件路径
+    pub tokens: String,
+    /// Silero VAD 模型路径
+    pub vad_model: String,
+    /// 采样率
+    pub sample_rate: i32,
+    /// 特征维度
+    pub feature_dim: i32,
+    /// 线程数
+    pub num_threads: i32,
+    /// VAD 判定为语音的阈值
+    pub vad_threshold: f32,
+    /// 语音段结束所需的最小静音时长 (秒)
+    pub min_silence_duration: f32,
+    /// 最小语音段时长 (秒)
+    pub min_speech_duration: f32,
+    /// 最大语音段时长 (秒)，超过后强制切分
+    pub max_speech_duration: f32,
+    /// 是否开启调试模式
+    pub debug: bool,
+}
+
+impl Default for OfflineRecognizerConfig {
+    fn default() -> Self {
+        Self {
+            model: OfflineModel::SenseVoice {
+                model: String::new(),
+                language: String::new(),
+            },
+            tokens: String::new(),
+            vad_model: String::new(),
+            sample_rate: 16000,
+            feature_dim: 80,
+            num_threads: 2,
+            vad_threshold: 0.5,
+            min_silence_duration: 0.5,
+            min_speech_duration: 0.25,
+            max_speech_duration: 20.0,
+            debug: false,
+        }
+    }
+}
+
+/// OfflineRecognizer 封装
+///
+/// VAD + 离线识别器，每个语音段输出一条最终结果
+pub struct OfflineRecognizer {
+    recognizer: *const sherpa_rs_sys::SherpaOnnxOfflineRecognizer,
+    vad: *const sherpa_rs_sys::SherpaOnnxVoiceActivityDetector,
+    sample_rate: i32,
+}
+
+// 与 OnlineRecognizer 相同，只能由一个线程使用 (不实现 Sync)
+unsafe impl Send for OfflineRecognizer {}
+
+impl OfflineRecognizer {
+    /// 创建新的 OfflineRecognizer (同时加载 VAD 模型)
+    pub fn new(config: OfflineRecognizerConfig) -> Result<Self, String> {
+        // 准备 C 字符串 (模型类型不需要的文件使用空字符串)
+        let (encoder, decoder, model, language) = match &config.model {
+            OfflineModel::Whisper {
+                encoder,
+                decoder,
+                language,
+            } => (encoder.as_str(), decoder.as_str(), "", language.as_str()),
+            OfflineModel::SenseVoice { model, language } => {
+                let language = if language.is_empty() {
+                    "auto"
+                } else {
+                    language.as_str()
+                };
+                ("", "", model.as_str(), language)
+            }
+        };
+        let encoder = CString::new(encoder).map_err(|e| e.to_string())?;
+        let decoder = CString::new(decoder).map_err(|e| e.to_string())?;
+        let model = CString::new(model).map_err(|e| e.to_string())?;
+        let language = CString::new(language).map_err(|e| e.to_string())?;
+        let task = CString::new("transcribe").map_err(|e| e.to_string())?;
+        let tokens = CString::new(config.tokens.as_str()).map_err(|e| e.to_string())?;
+        let vad_model = CString::new(config.vad_model.as_str()).map_err(|e| e.to_string())?;
+        let decoding_method = CString::new("greedy_search").map_err(|e| e.to_string())?;
+        let provider = CString::new("cpu").map_err(|e| e.to_string())?;
+
+        unsafe {
+            // 根据模型类型填充对应的配置，其他类型保持为空
+            let mut model_config = sherpa_rs_sys::SherpaOnnxOfflineModelConfig {
+                tokens: tokens.as_ptr(),
+                num_threads: config.num_threads,
+                debug: if config.debug { 1 } else { 0 },
+                provider: provider.as_ptr(),
+                ..std::mem::zeroed()
+            };
+            match &config.model {
+                OfflineModel::Whisper { .. } => {
+                    model_config.whisper = sherpa_rs_sys::SherpaOnnxOfflineWhisperModelConfig {
+                        encoder: encoder.as_ptr(),
+                        decoder: decoder.as_ptr(),
+                        language: language.as_ptr(),
+                        task: task.as_ptr(),
+                        tail_paddings: -1,
+                    };
+                }
+                OfflineModel::SenseVoice { .. } => {
+                    model_config.sense_voice =
+                        sherpa_rs_sys::SherpaOnnxOfflineSenseVoiceModelConfig {
+                            model: model.as_ptr(),
+                            language: language.as_ptr(),
+                            use_itn: 1,
+                        };
+                }
+            }
+
+            // 识别器配置
+            let recognizer_config = sherpa_rs_sys::SherpaOnnxOfflineRecognizerConfig {
+                feat_config: sherpa_rs_sys::SherpaOnnxFeatureConfig {
+                    sample_rate: config.sample_rate,
+                    feature_dim: config.feature_dim,
+                },
+                model_config,
+                decoding_method: decoding_method.as_ptr(),
+                max_active_paths: 4,
+                ..std::mem::zeroed()
+            };
+
+            let recognizer = sherpa_rs_sys::SherpaOnnxCreateOfflineRecognizer(&recognizer_config);
+            if recognizer.is_null() {
+                return Err("无法创建离线识别器，请检查模型文件".to_string());
+            }
+
+            // VAD 配置
+            let vad_config = sherpa_rs_sys::SherpaOnnxVadModelConfig {
+                silero_vad: sherpa_rs_sys::SherpaOnnxSileroVadModelConfig {
+                    model: vad_model.as_ptr(),
+                    threshold: config.vad_threshold,
+                    min_silence_duration: config.min_silence_duration,
+                    min_speech_duration: config.min_speech_duration,
+                    window_size: 512,
+                    max_speech_duration: config.max_speech_duration,
+                },
+                sample_rate: config.sample_rate,
+                num_threads: 1,
+                provider: provider.as_ptr(),
+                debug: if config.debug { 1 } else { 0 },
+                ..std::mem::zeroed()
+            };
+
+            // 缓冲区需要容纳最长的语音段
+            let buffer_size_in_seconds = config.max_speech_duration + 10.0;
+            let vad = sherpa_rs_sys::SherpaOnnxCreateVoiceActivityDetector(
+                &vad_config,
+                buffer_size_in_seconds,
+            );
+            if vad.is_null() {
+                sherpa_rs_sys::SherpaOnnxDestroyOfflineRecognizer(recognizer);
+                return Err("无法创建语音活动检测器，请检查 Silero VAD 模型".to_string());
+            }
+
+            Ok(Self {
+                recognizer,
+                vad,
+                sample_rate: config.sample_rate,
+            })
+        }
+    }
+
+    /// 处理音频并返回已结束语音段的识别结果
+    pub fn process(&mut self, samples: &[f32]) -> Vec<SpeechSegment> {
+        if !samples.is_empty() {
+            unsafe {
+                sherpa_rs_sys::SherpaOnnxVoiceActivityDetectorAcceptWaveform(
+                    self.vad,
+                    samples.as_ptr(),
+                    samples.len() as i32,
+                );
+            }
+        }
+
+        self.drain_segments()
+    }
+
+    /// 强制结束当前语音段 (音频源结束时调用)，返回剩余的识别结果
+    pub fn flush(&mut self) -> Vec<SpeechSegment> {
+        unsafe {
+            sherpa_rs_sys::SherpaOnnxVoiceActivityDetectorFlush(self.vad);
+        }
+
+        self.drain_segments()
+    }
+
+    /// 模型采样率
+    pub fn sample_rate(&self) -> i32 {
+        self.sample_rate
+    }
+
+    /// 清空 VAD 中尚未结束的语音段 (复用识别器开始新的识别时调用)
+    pub fn reset(&mut self) {
+        unsafe {
+            sherpa_rs_sys::SherpaOnnxVoiceActivityDetectorReset(self.vad);
+        }
+    }
+
+    /// 依次解码 VAD 队列中已完成的语音段
+    fn drain_segments(&mut self) -> Vec<SpeechSegment> {
+        let mut segments = Vec::new();
+
+        unsafe {
+            while sherpa_rs_sys::SherpaOnnxVoiceActivityDetectorEmpty(self.vad) == 0 {
+                let segment = sherpa_rs_sys::SherpaOnnxVoiceActivityDetectorFront(self.vad);
+                if !segment.is_null() {
+                    let samples =
+                        std::slice::from_raw_parts((*segment).samples, (*segment).n as usize);
+                    let result = self.decode(samples);
+                    if !result.text.is_empty() {
+                        segments.push(SpeechSegment {
+                            start: (*segment).start as f32 / self.sample_rate as f32,
+                            duration: samples.len() as f32 / self.sample_rate as f32,
+                            result,
+                        });
+                    }
+                    sherpa_rs_sys::SherpaOnnxDestroySpeechSegment(segment);
+                }
+                sherpa_rs_sys::SherpaOnnxVoiceActivityDetectorPop(self.vad);
+            }
+        }
+
+        segments
+    }
+
+    /// 使用离线模型解码一个语音段
+    fn decode(&self, samples: &[f32]) -> RecognitionResult {
+        unsafe {
+            let stream = sherpa_rs_sys::SherpaOnnxCreateOfflineStream(self.recognizer);
+            if stream.is_null() {
+                return RecognitionResult::default();
+            }
+
+            sherpa_rs_sys::SherpaOnnxAcceptWaveformOffline(
+                stream,
+                self.sample_rate,
+                samples.as_ptr(),
+                samples.len() as i32,
+            );
+            sherpa_rs_sys::SherpaOnnxDecodeOfflineStream(self.recognizer, stream);
+
+            let result = sherpa_rs_sys::SherpaOnnxGetOfflineStreamResult(stream);
+            let mut recognition_result = if result.is_null() {
+                RecognitionResult::default()
+            } else {
+                RecognitionResult::from_raw(
+                    (*result).text,
+                    (*result).tokens_arr,
+                    (*result).timestamps,
+                    (*result).count,
+                )
+            };
+            recognition_result.text = recognition_result.text.trim().to_string();
+
+            if !result.is_null() {
+                sherpa_rs_sys::SherpaOnnxDestroyOfflineRecognizerResult(result);
+            }
+            sherpa_rs_sys::SherpaOnnxDestroyOfflineStream(stream);
+            recognition_result
+        }
+    }
+}
+
+impl Drop for OfflineRecognizer {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.vad.is_null() {
+                sherpa_rs_sys::SherpaOnnxDestroyVoiceActivityDetector(self.vad);
+            }
+            if !self.recognizer.is_null() {
+                sherpa_rs_sys::SherpaOnnxDestroyOfflineRecognizer(self.recognizer);
+            }
+        }
+    }
+}
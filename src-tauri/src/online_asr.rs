@@ -5,18 +5,80 @@
 //! 并内置 endpoint 检测，无需额外的 VAD。
 
 use sherpa_rs::sherpa_rs_sys;
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
 use std::ptr;
 
+/// 识别结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecognitionResult {
+    /// 识别文本
+    pub text: String,
+    /// 识别出的 token
+    pub tokens: Vec<String>,
+    /// 每个 token 的开始时间 (秒，相对当前语句开始；模型不支持时为空)
+    pub timestamps: Vec<f32>,
+}
+
+impl RecognitionResult {
+    /// 从 sherpa-onnx 结果中的字段构建
+    ///
+    /// # Safety
+    /// 指针必须来自 sherpa-onnx 返回的同一个结果，且数组长度为 `count`
+    pub unsafe fn from_raw(
+        text: *const c_char,
+        tokens_arr: *const *const c_char,
+        timestamps: *const f32,
+        count: i32,
+    ) -> Self {
+        let text = if text.is_null() {
+            String::new()
+        } else {
+            CStr::from_ptr(text).to_string_lossy().to_string()
+        };
+
+        let count = count.max(0) as usize;
+        let tokens = if tokens_arr.is_null() {
+            Vec::new()
+        } else {
+            std::slice::from_raw_parts(tokens_arr, count)
+                .iter()
+                .filter(|token| !token.is_null())
+                .map(|&token| CStr::from_ptr(token).to_string_lossy().to_string())
+                .collect()
+        };
+        let timestamps = if timestamps.is_null() {
+            Vec::new()
+        } else {
+            std::slice::from_raw_parts(timestamps, count).to_vec()
+        };
+
+        Self { text, tokens, timestamps }
+    }
+}
+
+/// 流式模型文件
+#[derive(Debug, Clone, PartialEq)]
+pub enum OnlineModel {
+    /// Transducer 模型 (encoder/decoder/joiner)
+    Transducer {
+        encoder: String,
+        decoder: String,
+        joiner: String,
+    },
+    /// 流式 Paraformer 模型 (encoder/decoder)
+    Paraformer { encoder: String, decoder: String },
+    /// 流式 Zipformer2 CTC 模型 (单一模型文件)
+    Zipformer2Ctc { model: String },
+    /// 流式 NeMo CTC 模型 (单一模型文件)
+    NemoCtc { model: String },
+}
+
 /// OnlineRecognizer 配置
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct OnlineRecognizerConfig {
-    /// encoder 模型路径
-    pub encoder: String,
-    /// decoder 模型路径
-    pub decoder: String,
-    /// joiner 模型路径
-    pub joiner: String,
+    /// 模型文件
+    pub model: OnlineModel,
     /// tokens 文件路径
     pub tokens: String,
     /// 采样率
@@ -35,6 +97,14 @@ pub struct OnlineRecognizerConfig {
     pub rule3_min_utterance_length: f32,
     /// 解码方法
     pub decoding_method: String,
+    /// modified_beam_search 保留的最大路径数
+    pub max_active_paths: i32,
+    /// 建模单元 (cjkchar / bpe / cjkchar+bpe)，热词编码时使用
+    pub modeling_unit: String,
+    /// bpe.vocab 文件路径 (可选)
+    pub bpe_vocab: Option<String>,
+    /// CTC 模型的 HLG 解码图路径 (可选)
+    pub hlg_graph: Option<String>,
     /// 是否开启调试模式
     pub debug: bool,
 }
@@ -42,9 +112,11 @@ pub struct OnlineRecognizerConfig {
 impl Default for OnlineRecognizerConfig {
     fn default() -> Self {
         Self {
-            encoder: String::new(),
-            decoder: String::new(),
-            joiner: String::new(),
+            model: OnlineModel::Transducer {
+                encoder: String::new(),
+                decoder: String::new(),
+                joiner: String::new(),
+            },
             tokens: String::new(),
             sample_rate: 16000,
             feature_dim: 80,
@@ -54,6 +126,10 @@ impl Default for OnlineRecognizerConfig {
             rule2_min_trailing_silence: 1.2,
             rule3_min_utterance_length: 20.0,
             decoding_method: "greedy_search".to_string(),
+            max_active_paths: 4,
+            modeling_unit: "cjkchar".to_string(),
+            bpe_vocab: None,
+            hlg_graph: None,
             debug: false,
         }
     }
@@ -68,28 +144,83 @@ pub struct OnlineRecognizer {
     sample_rate: i32,
 }
 
-// 手动实现 Send 和 Sync，因为 sherpa-onnx 内部是线程安全的
+// 识别器可以移动到识别线程中使用，但流状态会被修改，不能在多个线程间共享 (不实现 Sync)
 unsafe impl Send for OnlineRecognizer {}
-unsafe impl Sync for OnlineRecognizer {}
 
 impl OnlineRecognizer {
     /// 创建新的 OnlineRecognizer
     pub fn new(config: OnlineRecognizerConfig) -> Result<Self, String> {
-        // 准备 C 字符串
-        let encoder = CString::new(config.encoder.as_str()).map_err(|e| e.to_string())?;
-        let decoder = CString::new(config.decoder.as_str()).map_err(|e| e.to_string())?;
-        let joiner = CString::new(config.joiner.as_str()).map_err(|e| e.to_string())?;
+        // 准备 C 字符串 (模型类型不需要的文件使用空字符串)
+        let (encoder, decoder, joiner, model) = match &config.model {
+            OnlineModel::Transducer { encoder, decoder, joiner } => {
+                (encoder.as_str(), decoder.as_str(), joiner.as_str(), "")
+            }
+            OnlineModel::Paraformer { encoder, decoder } => {
+                (encoder.as_str(), decoder.as_str(), "", "")
+            }
+            OnlineModel::Zipformer2Ctc { model } | OnlineModel::NemoCtc { model } => {
+                ("", "", "", model.as_str())
+            }
+        };
+        let encoder = CString::new(encoder).map_err(|e| e.to_string())?;
+        let decoder = CString::new(decoder).map_err(|e| e.to_string())?;
+        let joiner = CString::new(joiner).map_err(|e| e.to_string())?;
+        let model = CString::new(model).map_err(|e| e.to_string())?;
+        let hlg_graph = config
+            .hlg_graph
+            .as_deref()
+            .map(CString::new)
+            .transpose()
+            .map_err(|e| e.to_string())?;
         let tokens = CString::new(config.tokens.as_str()).map_err(|e| e.to_string())?;
         let decoding_method = CString::new(config.decoding_method.as_str()).map_err(|e| e.to_string())?;
         let provider = CString::new("cpu").map_err(|e| e.to_string())?;
+        let modeling_unit = CString::new(config.modeling_unit.as_str()).map_err(|e| e.to_string())?;
+        let bpe_vocab = config
+            .bpe_vocab
+            .as_deref()
+            .map(CString::new)
+            .transpose()
+            .map_err(|e| e.to_string())?;
 
         unsafe {
-            // 构建 Transducer 模型配置
-            let transducer_config = sherpa_rs_sys::SherpaOnnxOnlineTransducerModelConfig {
-                encoder: encoder.as_ptr(),
-                decoder: decoder.as_ptr(),
-                joiner: joiner.as_ptr(),
-            };
+            // 根据模型类型填充对应的配置，其他类型保持为空
+            let mut transducer_config: sherpa_rs_sys::SherpaOnnxOnlineTransducerModelConfig = std::mem::zeroed();
+            let mut paraformer_config: sherpa_rs_sys::SherpaOnnxOnlineParaformerModelConfig = std::mem::zeroed();
+            let mut zipformer2_ctc_config: sherpa_rs_sys::SherpaOnnxOnlineZipformer2CtcModelConfig = std::mem::zeroed();
+            let mut nemo_ctc_config: sherpa_rs_sys::SherpaOnnxOnlineNemoCtcModelConfig = std::mem::zeroed();
+            match &config.model {
+                OnlineModel::Transducer { .. } => {
+                    transducer_config = sherpa_rs_sys::SherpaOnnxOnlineTransducerModelConfig {
+                        encoder: encoder.as_ptr(),
+                        decoder: decoder.as_ptr(),
+                        joiner: joiner.as_ptr(),
+                    };
+                }
+                OnlineModel::Paraformer { .. } => {
+                    paraformer_config = sherpa_rs_sys::SherpaOnnxOnlineParaformerModelConfig {
+                        encoder: encoder.as_ptr(),
+                        decoder: decoder.as_ptr(),
+                    };
+                }
+                OnlineModel::Zipformer2Ctc { .. } => {
+                    zipformer2_ctc_config = sherpa_rs_sys::SherpaOnnxOnlineZipformer2CtcModelConfig {
+                        model: model.as_ptr(),
+                    };
+                }
+                OnlineModel::NemoCtc { .. } => {
+                    nemo_ctc_config = sherpa_rs_sys::SherpaOnnxOnlineNemoCtcModelConfig {
+                        model: model.as_ptr(),
+                    };
+                }
+            }
+
+            // CTC 模型可选的 HLG 解码图
+            let mut ctc_fst_decoder_config: sherpa_rs_sys::SherpaOnnxOnlineCtcFstDecoderConfig = std::mem::zeroed();
+            if let Some(graph) = &hlg_graph {
+                ctc_fst_decoder_config.graph = graph.as_ptr();
+                ctc_fst_decoder_config.max_active = 3000;
+            }
 
             // 构建模型配置
             let model_config = sherpa_rs_sys::SherpaOnnxOnlineModelConfig {
@@ -98,15 +229,15 @@ impl OnlineRecognizer {
                 num_threads: config.num_threads,
                 provider: provider.as_ptr(),
                 debug: if config.debug { 1 } else { 0 },
-                // 其他模型类型设为默认/空
-                paraformer: std::mem::zeroed(),
-                zipformer2_ctc: std::mem::zeroed(),
+                paraformer: paraformer_config,
+                zipformer2_ctc: zipformer2_ctc_config,
+                // 其他配置设为默认/空
                 model_type: ptr::null(),
-                modeling_unit: ptr::null(),
-                bpe_vocab: ptr::null(),
+                modeling_unit: modeling_unit.as_ptr(),
+                bpe_vocab: bpe_vocab.as_ref().map_or(ptr::null(), |v| v.as_ptr()),
                 tokens_buf: ptr::null(),
                 tokens_buf_size: 0,
-                nemo_ctc: std::mem::zeroed(),
+                nemo_ctc: nemo_ctc_config,
             };
 
             // 特征配置
@@ -120,15 +251,16 @@ impl OnlineRecognizer {
                 feat_config,
                 model_config,
                 decoding_method: decoding_method.as_ptr(),
-                max_active_paths: 4,
+                max_active_paths: config.max_active_paths,
                 enable_endpoint: if config.enable_endpoint { 1 } else { 0 },
                 rule1_min_trailing_silence: config.rule1_min_trailing_silence,
                 rule2_min_trailing_silence: config.rule2_min_trailing_silence,
                 rule3_min_utterance_length: config.rule3_min_utterance_length,
                 // 其他配置
                 hotwords_file: ptr::null(),
-                hotwords_score: 0.0,
-                ctc_fst_decoder_config: std::mem::zeroed(),
+                // 热词在创建流时传入 (见 set_hotwords)，每个热词都带有加分，不使用这里的默认值
+                hotwords_score: 1.5,
+                ctc_fst_decoder_config,
                 hotwords_buf: ptr::null(),
                 hotwords_buf_size: 0,
                 rule_fsts: ptr::null(),
@@ -140,14 +272,14 @@ impl OnlineRecognizer {
             // 创建识别器
             let recognizer = sherpa_rs_sys::SherpaOnnxCreateOnlineRecognizer(&recognizer_config);
             if recognizer.is_null() {
-                return Err("Failed to create OnlineRecognizer. Please check your model files.".to_string());
+                return Err("无法创建流式识别器，请检查模型文件".to_string());
             }
 
             // 创建流
             let stream = sherpa_rs_sys::SherpaOnnxCreateOnlineStream(recognizer);
             if stream.is_null() {
                 sherpa_rs_sys::SherpaOnnxDestroyOnlineRecognizer(recognizer);
-                return Err("Failed to create OnlineStream".to_string());
+                return Err("无法创建识别流".to_string());
             }
 
             Ok(Self {
@@ -158,8 +290,31 @@ impl OnlineRecognizer {
         }
     }
 
+    /// 更换热词 (每行一个，空字符串表示不使用热词)
+    ///
+    /// 热词属于识别流，更换时创建新的流，当前语句的状态随之丢弃，因此应在语句之间调用。
+    /// sherpa-onnx 只有 Transducer 模型支持热词，其他模型只能传入空字符串
+    pub fn set_hotwords(&mut self, hotwords: &str) -> Result<(), String> {
+        let hotwords = CString::new(hotwords).map_err(|e| e.to_string())?;
+
+        unsafe {
+            let stream = if hotwords.as_bytes().is_empty() {
+                sherpa_rs_sys::SherpaOnnxCreateOnlineStream(self.recognizer)
+            } else {
+                sherpa_rs_sys::SherpaOnnxCreateOnlineStreamWithHotwords(self.recognizer, hotwords.as_ptr())
+            };
+            if stream.is_null() {
+                return Err("无法创建识别流".to_string());
+            }
+
+            sherpa_rs_sys::SherpaOnnxDestroyOnlineStream(self.stream);
+            self.stream = stream;
+        }
+        Ok(())
+    }
+
     /// 接受音频波形数据
-    pub fn accept_waveform(&self, samples: &[f32]) {
+    pub fn accept_waveform(&mut self, samples: &[f32]) {
         if samples.is_empty() {
             return;
         }
@@ -182,7 +337,7 @@ impl OnlineRecognizer {
     }
 
     /// 解码当前数据
-    pub fn decode(&self) {
+    pub fn decode(&mut self) {
         unsafe {
             sherpa_rs_sys::SherpaOnnxDecodeOnlineStream(self.recognizer, self.stream);
         }
@@ -196,37 +351,41 @@ impl OnlineRecognizer {
     }
 
     /// 重置流状态 (用于开始新的句子)
-    pub fn reset(&self) {
+    pub fn reset(&mut self) {
         unsafe {
             sherpa_rs_sys::SherpaOnnxOnlineStreamReset(self.recognizer, self.stream);
         }
     }
 
-    /// 获取当前识别结果
-    pub fn get_result(&self) -> String {
+    /// 获取当前识别结果 (文本、token 及其相对当前语句开始的时间)
+    pub fn get_result(&self) -> RecognitionResult {
         unsafe {
             let result = sherpa_rs_sys::SherpaOnnxGetOnlineStreamResult(self.recognizer, self.stream);
             if result.is_null() {
-                return String::new();
+                return RecognitionResult::default();
             }
 
-            let text = if (*result).text.is_null() {
-                String::new()
-            } else {
-                std::ffi::CStr::from_ptr((*result).text)
-                    .to_string_lossy()
-                    .to_string()
-            };
+            let recognition_result = RecognitionResult::from_raw(
+                (*result).text,
+                (*result).tokens_arr,
+                (*result).timestamps,
+                (*result).count,
+            );
 
             sherpa_rs_sys::SherpaOnnxDestroyOnlineRecognizerResult(result);
-            text
+            recognition_result
         }
     }
 
+    /// 采样率
+    pub fn sample_rate(&self) -> i32 {
+        self.sample_rate
+    }
+
     /// 处理音频并返回识别结果
     /// 
-    /// 返回 (text, is_endpoint)
-    pub fn process(&self, samples: &[f32]) -> (String, bool) {
+    /// 返回 (result, is_endpoint)
+    pub fn process(&mut self, samples: &[f32]) -> (RecognitionResult, bool) {
         // 接受波形
         self.accept_waveform(samples);
 
@@ -236,10 +395,10 @@ impl OnlineRecognizer {
         }
 
         // 获取结果
-        let text = self.get_result();
+        let result = self.get_result();
         let is_endpoint = self.is_endpoint();
 
-        (text, is_endpoint)
+        (result, is_endpoint)
     }
 }
 
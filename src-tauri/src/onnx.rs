@@ -0,0 +1,248 @@
+//! ONNX 模型元数据读取
+//!
+//! 只解析 `ModelProto` 的顶层字段 (protobuf 编码)：计算图等大字段直接跳过，
+//! 因此即使是数百 MB 的模型也只需要读取很少的数据。
+//! sherpa-onnx 导出的模型把模型类型、是否流式、特征维度等信息写在 `metadata_props` 中。
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// `ModelProto` 的字段编号
+const FIELD_IR_VERSION: u64 = 1;
+const FIELD_PRODUCER_NAME: u64 = 2;
+const FIELD_GRAPH: u64 = 7;
+const FIELD_METADATA_PROPS: u64 = 14;
+
+/// 字符串字段 (producer_name、元数据) 的长度上限，超过时视为文件损坏
+const MAX_STRING_LEN: u64 = 1 << 20;
+
+/// ONNX 模型的基本信息
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OnnxModelInfo {
+    pub ir_version: u64,
+    /// 导出工具 (如 "pytorch")
+    pub producer_name: String,
+    /// 自定义元数据 (`metadata_props`)
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl OnnxModelInfo {
+    /// 读取模型文件，文件不是有效的 ONNX 模型时返回 `InvalidData` 错误
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        Self::parse(BufReader::new(file), len)
+    }
+
+    /// 从 `reader` 解析长度为 `len` 的模型
+    pub fn parse<R: Read + Seek>(mut reader: R, len: u64) -> io::Result<Self> {
+        let mut info = Self::default();
+        let mut has_ir_version = false;
+        let mut has_graph = false;
+        let mut pos = 0;
+
+        while pos < len {
+            let key = read_varint(&mut reader, &mut pos)?;
+            let (field, wire_type) = (key >> 3, key & 7);
+
+            match wire_type {
+                // varint
+                0 => {
+                    let value = read_varint(&mut reader, &mut pos)?;
+                    if field == FIELD_IR_VERSION {
+                        info.ir_version = value;
+                        has_ir_version = true;
+                    }
+                }
+                // 64 位 / 32 位定长
+                1 | 5 => {
+                    let size = if wire_type == 1 { 8 } else { 4 };
+                    skip(&mut reader, &mut pos, size, len)?;
+                }
+                // 长度前缀
+                2 => {
+                    let size = read_varint(&mut reader, &mut pos)?;
+                    if size > len - pos {
+                        return Err(invalid_data("truncated field"));
+                    }
+                    match field {
+                        FIELD_PRODUCER_NAME => {
+                            info.producer_name = read_string(&mut reader, &mut pos, size)?;
+                        }
+                        FIELD_METADATA_PROPS => {
+                            let entry = read_bytes(&mut reader, &mut pos, size)?;
+                            let (key, value) = parse_entry(&entry)?;
+                            info.metadata.insert(key, value);
+                        }
+                        _ => {
+                            has_graph |= field == FIELD_GRAPH;
+                            skip(&mut reader, &mut pos, size, len)?;
+                        }
+                    }
+                }
+                _ => return Err(invalid_data("unsupported wire type")),
+            }
+        }
+
+        if !has_ir_version || !has_graph {
+            return Err(invalid_data("missing ir_version or graph"));
+        }
+        Ok(info)
+    }
+
+    /// 读取元数据中的整数
+    pub fn metadata_int(&self, key: &str) -> Option<i64> {
+        self.metadata.get(key)?.trim().parse().ok()
+    }
+
+    /// 元数据中的模型类型 (`model_type`，转为小写)
+    pub fn model_type(&self) -> Option<String> {
+        Some(self.metadata.get("model_type")?.trim().to_lowercase())
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("not a valid ONNX model: {}", message),
+    )
+}
+
+fn read_varint(reader: &mut impl Read, pos: &mut u64) -> io::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0u8];
+        reader.read_exact(&mut byte).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => invalid_data("truncated varint"),
+            _ => e,
+        })?;
+        *pos += 1;
+        value |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid_data("varint too long"))
+}
+
+fn skip(reader: &mut impl Seek, pos: &mut u64, size: u64, len: u64) -> io::Result<()> {
+    if size > len - *pos {
+        return Err(invalid_data("truncated field"));
+    }
+    reader.seek(SeekFrom::Current(size as i64))?;
+    *pos += size;
+    Ok(())
+}
+
+fn read_bytes(reader: &mut impl Read, pos: &mut u64, size: u64) -> io::Result<Vec<u8>> {
+    if size > MAX_STRING_LEN {
+        return Err(invalid_data("string field too long"));
+    }
+    let mut buf = vec![0u8; size as usize];
+    reader.read_exact(&mut buf)?;
+    *pos += size;
+    Ok(buf)
+}
+
+fn read_string(reader: &mut impl Read, pos: &mut u64, size: u64) -> io::Result<String> {
+    let bytes = read_bytes(reader, pos, size)?;
+    String::from_utf8(bytes).map_err(|_| invalid_data("string is not UTF-8"))
+}
+
+/// 解析 `StringStringEntryProto { key = 1; value = 2; }`
+fn parse_entry(bytes: &[u8]) -> io::Result<(String, String)> {
+    let mut reader = io::Cursor::new(bytes);
+    let len = bytes.len() as u64;
+    let mut pos = 0;
+    let (mut key, mut value) = (String::new(), String::new());
+
+    while pos < len {
+        let tag = read_varint(&mut reader, &mut pos)?;
+        if tag & 7 != 2 {
+            return Err(invalid_data("invalid metadata entry"));
+        }
+        let size = read_varint(&mut reader, &mut pos)?;
+        if size > len - pos {
+            return Err(invalid_data("truncated metadata entry"));
+        }
+        let text = read_string(&mut reader, &mut pos, size)?;
+        match tag >> 3 {
+            1 => key = text,
+            2 => value = text,
+            _ => {}
+        }
+    }
+    Ok((key, value))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn varint(mut value: u64, out: &mut Vec<u8>) {
+        while value >= 0x80 {
+            out.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    fn bytes_field(field: u64, data: &[u8], out: &mut Vec<u8>) {
+        varint(field << 3 | 2, out);
+        varint(data.len() as u64, out);
+        out.extend_from_slice(data);
+    }
+
+    /// 构造只包含元数据和一个空计算图的 ONNX 模型 (供其他模块的测试使用)
+    pub(crate) fn fake_model(metadata: &[(&str, &str)]) -> Vec<u8> {
+        let mut out = Vec::new();
+        varint(FIELD_IR_VERSION << 3, &mut out);
+        varint(8, &mut out);
+        bytes_field(FIELD_PRODUCER_NAME, b"pytorch", &mut out);
+        bytes_field(FIELD_GRAPH, &[0u8; 300], &mut out);
+        for (key, value) in metadata {
+            let mut entry = Vec::new();
+            bytes_field(1, key.as_bytes(), &mut entry);
+            bytes_field(2, value.as_bytes(), &mut entry);
+            bytes_field(FIELD_METADATA_PROPS, &entry, &mut out);
+        }
+        out
+    }
+
+    fn parse(data: Vec<u8>) -> io::Result<OnnxModelInfo> {
+        let len = data.len() as u64;
+        OnnxModelInfo::parse(Cursor::new(data), len)
+    }
+
+    #[test]
+    fn test_parse_metadata() {
+        let info = parse(fake_model(&[
+            ("model_type", "zipformer2"),
+            ("decode_chunk_len", "32"),
+        ]))
+        .unwrap();
+
+        assert_eq!(info.ir_version, 8);
+        assert_eq!(info.producer_name, "pytorch");
+        assert_eq!(info.metadata["model_type"], "zipformer2");
+        assert_eq!(info.metadata_int("decode_chunk_len"), Some(32));
+        assert_eq!(info.metadata_int("model_type"), None);
+        assert_eq!(info.model_type().as_deref(), Some("zipformer2"));
+    }
+
+    #[test]
+    fn test_reject_invalid_files() {
+        // 文本文件 (例如误选了 tokens.txt)
+        assert!(parse(b"<blk> 0\n<sos/eos> 1\n".to_vec()).is_err());
+        assert!(parse(Vec::new()).is_err());
+
+        // 下载中断导致的截断
+        let mut truncated = fake_model(&[("model_type", "zipformer2")]);
+        truncated.truncate(100);
+        let error = parse(truncated).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,333 @@
+//! 识别器接口与缓存
+//!
+//! 加载 ONNX 模型需要数秒，因此识别器在停止识别后仍保留在识别引擎中，
+//! 下次开始识别时如果模型配置未变化则直接复用。
+//! 识别循环通过 `StreamingRecognizer` 使用流式识别器，嵌入方或测试可以提供自己的实现。
+
+use crate::config::{self, AppConfig, AsrModelConfig, AsrModelType, DecodingMethod};
+use crate::error::AppError;
+use crate::model_validation;
+use crate::offline_asr::{OfflineModel, OfflineRecognizer, OfflineRecognizerConfig};
+use crate::online_asr::{OnlineModel, OnlineRecognizer, OnlineRecognizerConfig, RecognitionResult};
+use std::path::Path;
+use std::sync::{Condvar, Mutex, MutexGuard};
+
+/// 识别器配置，同时作为缓存的 key
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecognizerConfig {
+    /// 流式识别器
+    Online(OnlineRecognizerConfig),
+    /// 离线识别器 (VAD 切分)
+    Offline(OfflineRecognizerConfig),
+}
+
+impl RecognizerConfig {
+    /// 根据当前选中的模型构建识别器配置
+    pub fn from_app_config(config: &AppConfig, models_dir: &Path) -> Result<Self, AppError> {
+        let asr_config = config.current_model().ok_or(AppError::ModelNotConfigured)?;
+        let vad_model = config.resolve_model_path(&config.vad_model, models_dir);
+        Self::from_model_config(asr_config, models_dir, &vad_model)
+    }
+
+    /// 模型采样率 (音频源需要重采样到该采样率)
+    pub fn sample_rate(&self) -> u32 {
+        match self {
+            Self::Online(config) => config.sample_rate as u32,
+            Self::Offline(config) => config.sample_rate as u32,
+        }
+    }
+
+    /// 根据模型配置构建识别器配置 (相对路径基于模型目录)
+    ///
+    /// 选项变化后配置也随之变化，下次开始识别时会重新加载识别器 (热词不属于识别器配置，见 `hotwords_for`)。
+    /// 先检查模型文件 (见 `model_validation`)，有问题时不再交给 sherpa-onnx 加载
+    fn from_model_config(
+        asr_config: &AsrModelConfig,
+        models_dir: &Path,
+        vad_model: &Path,
+    ) -> Result<Self, AppError> {
+        asr_config.validate().map_err(AppError::Config)?;
+
+        model_validation::validate_model(asr_config, models_dir, vad_model).check()?;
+
+        let options = &asr_config.options;
+        let resolve = |file: &str| models_dir.join(file).to_string_lossy().to_string();
+
+        if asr_config.model_type.is_offline() {
+            Ok(Self::Offline(OfflineRecognizerConfig {
+                model: offline_model_from_config(&asr_config.model_type, models_dir)?,
+                tokens: resolve(&asr_config.tokens),
+                vad_model: vad_model.to_string_lossy().to_string(),
+                sample_rate: asr_config.sample_rate as i32,
+                feature_dim: options.feature_dim,
+                num_threads: asr_config.num_threads,
+                ..Default::default()
+            }))
+        } else {
+            Ok(Self::Online(OnlineRecognizerConfig {
+                model: online_model_from_config(&asr_config.model_type, models_dir)?,
+                tokens: resolve(&asr_config.tokens),
+                sample_rate: asr_config.sample_rate as i32,
+                feature_dim: options.feature_dim,
+                num_threads: asr_config.num_threads,
+                enable_endpoint: options.enable_endpoint,
+                rule1_min_trailing_silence: options.rule1_min_trailing_silence,
+                rule2_min_trailing_silence: options.rule2_min_trailing_silence,
+                rule3_min_utterance_length: options.rule3_min_utterance_length,
+                decoding_method: options.decoding_method.as_str().to_string(),
+                max_active_paths: options.max_active_paths,
+                modeling_unit: options.modeling_unit.clone(),
+                bpe_vocab: Some(options.bpe_vocab.as_str())
+                    .filter(|v| !v.is_empty())
+                    .map(resolve),
+                hlg_graph: asr_config.model_type.hlg_graph().map(resolve),
+                debug: false, // 关闭 debug 模式减少日志输出
+            }))
+        }
+    }
+}
+
+/// 当前模型使用的热词内容 (见 `config::hotwords_buffer`)
+///
+/// sherpa-onnx 只有 Transducer 模型的 modified_beam_search 支持热词，其他情况返回空字符串。
+/// 热词在识别过程中也可以修改 (见 `Engine::set_hotwords`)，不需要重新加载识别器
+pub fn hotwords_for(config: &AppConfig) -> String {
+    match config.current_model() {
+        Some(model)
+            if matches!(model.model_type, AsrModelType::Transducer { .. })
+                && model.options.decoding_method == DecodingMethod::ModifiedBeamSearch =>
+        {
+            config::hotwords_buffer(&config.hotwords, config.hotwords_score)
+        }
+        _ => String::new(),
+    }
+}
+
+/// 流式识别器
+///
+/// 识别器包含流状态，同一时间只由一个识别线程使用
+pub trait StreamingRecognizer: Send {
+    /// 模型采样率
+    fn sample_rate(&self) -> i32;
+
+    /// 处理音频并返回当前语句的识别结果，返回 (result, is_endpoint)
+    fn process(&mut self, samples: &[f32]) -> (RecognitionResult, bool);
+
+    /// 开始新的语句 (endpoint 之后或开始新的识别时调用)
+    fn reset(&mut self);
+
+    /// 更换热词 (每行一个，空字符串表示不使用热词)，在语句之间调用。不支持热词的识别器忽略
+    fn set_hotwords(&mut self, _hotwords: &str) {}
+}
+
+impl StreamingRecognizer for OnlineRecognizer {
+    fn sample_rate(&self) -> i32 {
+        OnlineRecognizer::sample_rate(self)
+    }
+
+    fn process(&mut self, samples: &[f32]) -> (RecognitionResult, bool) {
+        OnlineRecognizer::process(self, samples)
+    }
+
+    fn reset(&mut self) {
+        OnlineRecognizer::reset(self)
+    }
+
+    fn set_hotwords(&mut self, hotwords: &str) {
+        if let Err(e) = OnlineRecognizer::set_hotwords(self, hotwords) {
+            eprintln!("Failed to set hotwords: {}", e);
+        }
+    }
+}
+
+/// 已加载的识别器
+pub enum LoadedRecognizer {
+    Online(Box<dyn StreamingRecognizer>),
+    Offline(OfflineRecognizer),
+}
+
+impl LoadedRecognizer {
+    /// 加载模型并创建识别器 (不经过缓存)
+    pub fn new(config: RecognizerConfig) -> Result<Self, AppError> {
+        match config {
+            RecognizerConfig::Online(config) => {
+                let recognizer = OnlineRecognizer::new(config).map_err(AppError::Recognizer)?;
+                Ok(Self::Online(Box::new(recognizer)))
+            }
+            RecognizerConfig::Offline(config) => OfflineRecognizer::new(config)
+                .map(Self::Offline)
+                .map_err(AppError::Recognizer),
+        }
+    }
+
+    /// 模型采样率
+    pub fn sample_rate(&self) -> u32 {
+        match self {
+            Self::Online(recognizer) => recognizer.sample_rate() as u32,
+            Self::Offline(recognizer) => recognizer.sample_rate() as u32,
+        }
+    }
+
+    /// 清除上次识别残留的状态
+    pub fn reset(&mut self) {
+        match self {
+            Self::Online(recognizer) => recognizer.reset(),
+            Self::Offline(recognizer) => recognizer.reset(),
+        }
+    }
+}
+
+/// 识别器缓存 (只保留最近一次使用的识别器)
+///
+/// 识别器同一时间只能由一个会话使用：开始识别时从缓存中取出，会话结束后放回
+#[derive(Default)]
+pub struct RecognizerCache {
+    state: Mutex<CacheState>,
+    /// 模型加载结束时通知等待的调用方
+    loaded: Condvar,
+}
+
+#[derive(Default)]
+struct CacheState {
+    cached: Option<(RecognizerConfig, LoadedRecognizer)>,
+    /// 正在加载模型 (加载时不持有锁)
+    loading: bool,
+}
+
+impl RecognizerCache {
+    /// 取出与配置匹配的识别器，配置变化时重新加载
+    ///
+    /// 取出后由调用方独占使用，用完后通过 `put_back` 放回。
+    /// `on_loading` 仅在真正加载模型时调用，参数表示是否正在加载
+    pub fn take(
+        &self,
+        config: &RecognizerConfig,
+        on_loading: impl FnMut(bool),
+    ) -> Result<LoadedRecognizer, AppError> {
+        let mut state = self.load(config, on_loading)?;
+        let (_, recognizer) = state
+            .cached
+            .take()
+            .ok_or_else(|| AppError::Internal("识别器缓存为空".to_string()))?;
+        Ok(recognizer)
+    }
+
+    /// 加载与配置匹配的识别器并留在缓存中 (预加载)
+    pub fn preload(
+        &self,
+        config: &RecognizerConfig,
+        on_loading: impl FnMut(bool),
+    ) -> Result<(), AppError> {
+        self.load(config, on_loading).map(|_| ())
+    }
+
+    /// 放回识别完成的识别器，下次使用相同配置时直接复用
+    pub fn put_back(&self, config: RecognizerConfig, recognizer: LoadedRecognizer) {
+        let replaced = self.lock().cached.replace((config, recognizer));
+        // 在锁外释放被替换的模型
+        drop(replaced);
+    }
+
+    /// 确保缓存中是与配置匹配的识别器，返回缓存的锁
+    ///
+    /// 模型在锁外加载，同一时间只加载一个模型：预加载与开始识别同时发生时，
+    /// 后来的调用等待加载结束后直接使用加载好的识别器
+    fn load(
+        &self,
+        config: &RecognizerConfig,
+        mut on_loading: impl FnMut(bool),
+    ) -> Result<MutexGuard<'_, CacheState>, AppError> {
+        let mut state = self.lock();
+        while state.loading {
+            state = self.loaded.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+
+        if state
+            .cached
+            .as_ref()
+            .is_some_and(|(cached_config, _)| cached_config == config)
+        {
+            return Ok(state);
+        }
+
+        // 先释放旧模型，避免两个模型同时占用内存
+        let old = state.cached.take();
+        state.loading = true;
+        drop(state);
+        drop(old);
+
+        on_loading(true);
+        let result = LoadedRecognizer::new(config.clone());
+        on_loading(false);
+
+        let mut state = self.lock();
+        state.loading = false;
+        self.loaded.notify_all();
+        state.cached = Some((config.clone(), result?));
+        Ok(state)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 将配置中的模型类型转换为流式识别器的模型文件 (相对路径基于模型目录)
+fn online_model_from_config(
+    model_type: &AsrModelType,
+    models_dir: &Path,
+) -> Result<OnlineModel, AppError> {
+    let resolve = |file: &str| models_dir.join(file).to_string_lossy().to_string();
+
+    match model_type {
+        AsrModelType::Transducer {
+            encoder,
+            decoder,
+            joiner,
+        } => Ok(OnlineModel::Transducer {
+            encoder: resolve(encoder),
+            decoder: resolve(decoder),
+            joiner: resolve(joiner),
+        }),
+        AsrModelType::Paraformer { encoder, decoder } => Ok(OnlineModel::Paraformer {
+            encoder: resolve(encoder),
+            decoder: resolve(decoder),
+        }),
+        AsrModelType::Zipformer2Ctc { model, .. } => Ok(OnlineModel::Zipformer2Ctc {
+            model: resolve(model),
+        }),
+        AsrModelType::NemoCtc { model, .. } => Ok(OnlineModel::NemoCtc {
+            model: resolve(model),
+        }),
+        _ => Err(AppError::Config(
+            "流式识别器只支持流式 (Transducer/Paraformer/CTC) 模型".to_string(),
+        )),
+    }
+}
+
+/// 将配置中的模型类型转换为离线识别器的模型文件 (相对路径基于模型目录)
+fn offline_model_from_config(
+    model_type: &AsrModelType,
+    models_dir: &Path,
+) -> Result<OfflineModel, AppError> {
+    let resolve = |file: &str| models_dir.join(file).to_string_lossy().to_string();
+
+    match model_type {
+        AsrModelType::Whisper {
+            encoder,
+            decoder,
+            language,
+        } => Ok(OfflineModel::Whisper {
+            encoder: resolve(encoder),
+            decoder: resolve(decoder),
+            language: language.clone(),
+        }),
+        AsrModelType::SenseVoice { model, language } => Ok(OfflineModel::SenseVoice {
+            model: resolve(model),
+            language: language.clone(),
+        }),
+        _ => Err(AppError::Config(
+            "离线识别器只支持 Whisper 和 SenseVoice 模型".to_string(),
+        )),
+    }
+}
@@ -0,0 +1,491 @@
+//! 字幕推送服务
+//!
+//! 可选的内置 HTTP 服务，把与前端相同的字幕事件以 JSON 广播给外部程序
+//! (OBS 浏览器源、仪表盘等)：
+//!
+//! - `/ws`: WebSocket，每条消息的格式为 `{"type": "...", "data": {...}}`
+//! - `/events`: Server-Sent Events，事件名为消息类型，数据为 JSON
+//! - `/overlay`: 使用用户 `subtitle.css` 的字幕页面，可直接作为 OBS 浏览器源
+//!
+//! 连接建立后先发送一条 `hello` 握手消息，描述当前模型与采样率。
+//! 设置了访问令牌时，客户端需要通过 `?token=` 或 `Authorization: Bearer` 提供令牌。
+
+use crate::config::ServerConfig;
+use crate::error::AppError;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use futures_util::stream::{self, Stream};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+
+/// 消息协议版本
+const PROTOCOL_VERSION: u32 = 1;
+
+/// 广播队列长度，客户端处理过慢时丢弃最旧的消息
+const CHANNEL_CAPACITY: usize = 256;
+
+/// 字幕页面 (通过 `/events` 接收字幕)
+const OVERLAY_HTML: &str = include_str!("../resources/overlay.html");
+
+/// 握手消息中的模型信息
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ModelInfo {
+    /// 模型 ID
+    pub id: String,
+    /// 模型名称
+    pub name: String,
+    /// 模型采样率
+    pub sample_rate: u32,
+    /// 是否是离线模型 (只输出最终结果)
+    pub offline: bool,
+}
+
+/// 服务状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerStatus {
+    /// 是否正在运行
+    pub running: bool,
+    /// 实际监听地址
+    pub address: Option<String>,
+    /// 当前连接的客户端数量
+    pub clients: usize,
+}
+
+/// 消息格式
+#[derive(Serialize)]
+struct Envelope<'a, T: Serialize> {
+    #[serde(rename = "type")]
+    kind: &'a str,
+    data: &'a T,
+}
+
+/// 广播给所有客户端的消息 (只序列化一次)
+struct Outgoing {
+    /// 消息类型 (SSE 事件名)
+    kind: String,
+    /// 消息数据的 JSON (SSE 数据)
+    data: String,
+    /// 完整消息的 JSON (WebSocket)
+    envelope: String,
+}
+
+impl Outgoing {
+    fn new<T: Serialize>(kind: &str, data: &T) -> serde_json::Result<Self> {
+        Ok(Self {
+            kind: kind.to_string(),
+            data: serde_json::to_string(data)?,
+            envelope: serde_json::to_string(&Envelope { kind, data })?,
+        })
+    }
+}
+
+/// 握手消息内容
+#[derive(Serialize)]
+struct Hello<'a> {
+    protocol: u32,
+    version: &'static str,
+    model: &'a ModelInfo,
+    sample_rate: u32,
+}
+
+/// 正在运行的服务
+struct RunningServer {
+    address: SocketAddr,
+    sender: broadcast::Sender<Arc<Outgoing>>,
+    shutdown: watch::Sender<bool>,
+    /// 服务任务，结束时监听的端口已释放
+    task: JoinHandle<()>,
+}
+
+/// 连接处理共享的数据
+#[derive(Clone)]
+struct Shared {
+    sender: broadcast::Sender<Arc<Outgoing>>,
+    token: String,
+    model: Arc<Mutex<ModelInfo>>,
+    style_path: Arc<Mutex<Option<PathBuf>>>,
+    shutdown: watch::Receiver<bool>,
+}
+
+impl Shared {
+    /// 当前的握手消息
+    fn hello(&self) -> serde_json::Result<Outgoing> {
+        let model = self
+            .model
+            .lock()
+            .map(|model| model.clone())
+            .unwrap_or_default();
+        Outgoing::new(
+            "hello",
+            &Hello {
+                protocol: PROTOCOL_VERSION,
+                version: env!("CARGO_PKG_VERSION"),
+                sample_rate: model.sample_rate,
+                model: &model,
+            },
+        )
+    }
+}
+
+/// 字幕推送服务
+#[derive(Default)]
+pub struct SubtitleServer {
+    running: Mutex<Option<RunningServer>>,
+    /// 保证同一时间只有一次启动/停止，避免并发修改配置时端口冲突
+    applying: tokio::sync::Mutex<()>,
+    model: Arc<Mutex<ModelInfo>>,
+    style_path: Arc<Mutex<Option<PathBuf>>>,
+}
+
+impl SubtitleServer {
+    /// 按配置启动服务 (已在运行时先停止并等待端口释放)，未启用时只停止
+    pub async fn apply_config(&self, config: &ServerConfig) -> Result<(), AppError> {
+        let _applying = self.applying.lock().await;
+        self.stop().await;
+        if !config.enabled {
+            return Ok(());
+        }
+
+        let address = config.socket_addr().map_err(AppError::Config)?;
+        let listener = tokio::net::TcpListener::bind(address)
+            .await
+            .map_err(|e| AppError::Server(format!("字幕推送服务无法监听 {}: {}", address, e)))?;
+        let address = listener
+            .local_addr()
+            .map_err(|e| AppError::Server(e.to_string()))?;
+
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        let (shutdown, shutdown_rx) = watch::channel(false);
+        let shared = Shared {
+            sender: sender.clone(),
+            token: config.token.clone(),
+            model: Arc::clone(&self.model),
+            style_path: Arc::clone(&self.style_path),
+            shutdown: shutdown_rx.clone(),
+        };
+
+        let app = Router::new()
+            .route("/", get(websocket_handler))
+            .route("/ws", get(websocket_handler))
+            .route("/events", get(events_handler))
+            .route("/overlay", get(overlay_handler))
+            .route("/overlay/subtitle.css", get(style_handler))
+            .with_state(shared);
+
+        let mut graceful = shutdown_rx;
+        let task = tokio::spawn(async move {
+            let result = axum::serve(listener, app)
+                .with_graceful_shutdown(async move {
+                    let _ = graceful.wait_for(|stopped| *stopped).await;
+                })
+                .await;
+            if let Err(e) = result {
+                eprintln!("[Server] Subtitle server error: {}", e);
+            }
+        });
+
+        println!("[Server] Subtitle server listening on http://{}", address);
+        let mut running = self.running.lock()?;
+        *running = Some(RunningServer {
+            address,
+            sender,
+            shutdown,
+            task,
+        });
+        Ok(())
+    }
+
+    /// 停止服务并断开所有客户端，等待服务任务结束 (监听的端口随之释放)
+    pub async fn stop(&self) {
+        let server = match self.running.lock() {
+            Ok(mut running) => running.take(),
+            Err(_) => None,
+        };
+        if let Some(server) = server {
+            let _ = server.shutdown.send(true);
+            if let Err(e) = server.task.await {
+                eprintln!("[Server] Subtitle server task failed: {}", e);
+            }
+            println!("[Server] Subtitle server on {} stopped", server.address);
+        }
+    }
+
+    /// 更新握手消息中的模型信息
+    pub fn set_model(&self, model: ModelInfo) {
+        if let Ok(mut current) = self.model.lock() {
+            *current = model;
+        }
+    }
+
+    /// 设置字幕页面使用的样式文件
+    pub fn set_style_path(&self, path: PathBuf) {
+        if let Ok(mut current) = self.style_path.lock() {
+            *current = Some(path);
+        }
+    }
+
+    /// 向所有客户端广播一条消息 (服务未运行或没有客户端时忽略)
+    pub fn broadcast<T: Serialize>(&self, kind: &str, data: &T) {
+        let Ok(running) = self.running.lock() else {
+            return;
+        };
+        let Some(server) = running.as_ref() else {
+            return;
+        };
+        if server.sender.receiver_count() == 0 {
+            return;
+        }
+
+        match Outgoing::new(kind, data) {
+            Ok(message) => {
+                let _ = server.sender.send(Arc::new(message));
+            }
+            Err(e) => eprintln!("[Server] Failed to serialize {} message: {}", kind, e),
+        }
+    }
+
+    /// 当前服务状态
+    pub fn status(&self) -> ServerStatus {
+        let running = self.running.lock().ok();
+        match running.as_ref().and_then(|running| running.as_ref()) {
+            Some(server) => ServerStatus {
+                running: true,
+                address: Some(server.address.to_string()),
+                clients: server.sender.receiver_count(),
+            },
+            None => ServerStatus {
+                running: false,
+                address: None,
+                clients: 0,
+            },
+        }
+    }
+}
+
+/// 认证参数
+#[derive(Deserialize)]
+struct AuthQuery {
+    token: Option<String>,
+}
+
+/// WebSocket 握手：检查令牌后升级连接
+async fn websocket_handler(
+    ws: WebSocketUpgrade,
+    Query(query): Query<AuthQuery>,
+    headers: HeaderMap,
+    State(shared): State<Shared>,
+) -> Response {
+    if !is_authorized(&shared.token, query.token.as_deref(), &headers) {
+        return (StatusCode::UNAUTHORIZED, "invalid token").into_response();
+    }
+
+    ws.on_upgrade(move |socket| handle_socket(socket, shared))
+}
+
+/// Server-Sent Events：检查令牌后持续推送消息
+async fn events_handler(
+    Query(query): Query<AuthQuery>,
+    headers: HeaderMap,
+    State(shared): State<Shared>,
+) -> Response {
+    if !is_authorized(&shared.token, query.token.as_deref(), &headers) {
+        return (StatusCode::UNAUTHORIZED, "invalid token").into_response();
+    }
+
+    let Ok(hello) = shared.hello() else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+
+    // 允许其他来源的页面 (如自制仪表盘) 订阅
+    (
+        [(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")],
+        Sse::new(event_stream(hello, shared)).keep_alive(KeepAlive::default()),
+    )
+        .into_response()
+}
+
+/// 把握手消息和广播消息转换为 SSE 事件流，服务停止时结束
+fn event_stream(hello: Outgoing, shared: Shared) -> impl Stream<Item = Result<Event, Infallible>> {
+    let receiver = shared.sender.subscribe();
+    let shutdown = shared.shutdown.clone();
+    let first = stream::once(async move { Ok(to_event(&hello)) });
+
+    let messages = stream::unfold(
+        (receiver, shutdown),
+        |(mut receiver, mut shutdown)| async move {
+            loop {
+                tokio::select! {
+                    message = receiver.recv() => match message {
+                        Ok(message) => return Some((Ok(to_event(&message)), (receiver, shutdown))),
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            eprintln!("[Server] Client is too slow, skipped {} messages", skipped);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    },
+                    _ = shutdown.changed() => return None,
+                }
+            }
+        },
+    );
+
+    stream::StreamExt::chain(first, messages)
+}
+
+fn to_event(message: &Outgoing) -> Event {
+    Event::default().event(&message.kind).data(&message.data)
+}
+
+/// 字幕页面 (令牌由页面从地址栏转发给 `/events`)
+async fn overlay_handler() -> Html<&'static str> {
+    Html(OVERLAY_HTML)
+}
+
+/// 字幕页面使用的样式 (每次请求重新读取，修改后刷新页面即可生效)
+async fn style_handler(State(shared): State<Shared>) -> Response {
+    let path = shared.style_path.lock().ok().and_then(|path| path.clone());
+    let css = match path {
+        Some(path) => tokio::fs::read_to_string(&path).await.unwrap_or_else(|e| {
+            eprintln!("[Server] Failed to read {}: {}", path.display(), e);
+            String::new()
+        }),
+        None => String::new(),
+    };
+
+    (
+        [
+            (header::CONTENT_TYPE, "text/css; charset=utf-8"),
+            (header::CACHE_CONTROL, "no-cache"),
+        ],
+        css,
+    )
+        .into_response()
+}
+
+/// 检查请求中的令牌 (未设置令牌时总是通过)
+fn is_authorized(token: &str, query_token: Option<&str>, headers: &HeaderMap) -> bool {
+    if token.is_empty() {
+        return true;
+    }
+
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    [query_token, bearer]
+        .into_iter()
+        .flatten()
+        .any(|provided| constant_time_eq(provided.trim().as_bytes(), token.as_bytes()))
+}
+
+/// 比较令牌，耗时与不匹配的位置无关
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// 单个客户端连接：先发送握手消息，然后转发广播直到断开或服务停止
+async fn handle_socket(mut socket: WebSocket, mut shared: Shared) {
+    // 先订阅，避免握手期间的消息丢失
+    let mut receiver = shared.sender.subscribe();
+
+    let Ok(hello) = shared.hello() else {
+        return;
+    };
+    if socket
+        .send(Message::Text(hello.envelope.into()))
+        .await
+        .is_err()
+    {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            message = receiver.recv() => match message {
+                Ok(message) => {
+                    if socket.send(Message::Text(message.envelope.clone().into())).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    eprintln!("[Server] Client is too slow, skipped {} messages", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                // 客户端消息只用于保持连接，Ping 由 axum 自动回复
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            // 服务停止时只会发送一次 true
+            _ = shared.shutdown.changed() => {
+                let _ = socket.send(Message::Close(None)).await;
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_reapply_same_address() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut config = ServerConfig {
+            enabled: true,
+            port,
+            ..Default::default()
+        };
+        let address = format!("127.0.0.1:{}", port);
+
+        // 重新应用同一个地址时必须等旧的服务释放端口，即使还有客户端连接
+        let server = SubtitleServer::default();
+        let mut clients = Vec::new();
+        for _ in 0..3 {
+            server.apply_config(&config).await.unwrap();
+            assert_eq!(server.status().address.as_deref(), Some(address.as_str()));
+            clients.push(
+                tokio::net::TcpStream::connect(address.as_str())
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        config.enabled = false;
+        server.apply_config(&config).await.unwrap();
+        assert!(!server.status().running);
+        assert!(tokio::net::TcpListener::bind(address.as_str())
+            .await
+            .is_ok());
+    }
+
+    #[test]
+    fn test_is_authorized() {
+        let headers = HeaderMap::new();
+        assert!(is_authorized("", None, &headers));
+        assert!(is_authorized("secret", Some("secret"), &headers));
+        assert!(!is_authorized("secret", Some("secreT"), &headers));
+        assert!(!is_authorized("secret", None, &headers));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        assert!(is_authorized("secret", None, &headers));
+        assert!(is_authorized("secret", Some("wrong"), &headers));
+    }
+}
@@ -0,0 +1,413 @@
+//! 会话字幕记录与导出
+//!
+//! 记录一次识别过程中的所有最终结果 (带音频内的开始/结束时间)，
+//! 可导出为 SRT、WebVTT 或纯文本。导出时按显示宽度自动换行：
+//! 中日韩字符按 2 个宽度计算且可在任意字符间断行，拉丁文字只在空格处断行。
+
+use serde::{Deserialize, Serialize};
+
+/// 一条字幕记录
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TranscriptEntry {
+    /// 开始时间 (秒，相对识别开始)
+    pub start: f32,
+    /// 结束时间 (秒，相对识别开始)
+    pub end: f32,
+    /// 文本
+    pub text: String,
+}
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptFormat {
+    /// SubRip 字幕
+    Srt,
+    /// WebVTT 字幕
+    Vtt,
+    /// 纯文本 (每句一行)
+    Text,
+}
+
+/// 换行规则
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct WrapOptions {
+    /// 每行最大显示宽度 (拉丁字符为 1，中日韩字符为 2)
+    pub max_line_width: usize,
+    /// 每条字幕最多行数，超出时拆分为多条字幕
+    pub max_lines: usize,
+}
+
+impl Default for WrapOptions {
+    fn default() -> Self {
+        // 42 个拉丁字符或 21 个汉字一行，每条最多两行
+        Self {
+            max_line_width: 42,
+            max_lines: 2,
+        }
+    }
+}
+
+/// 会话字幕记录
+#[derive(Debug, Clone, Default)]
+pub struct Transcript {
+    entries: Vec<TranscriptEntry>,
+}
+
+impl Transcript {
+    /// 添加一条最终结果
+    pub fn push(&mut self, start: f32, end: f32, text: &str) {
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+
+        self.entries.push(TranscriptEntry {
+            start,
+            end: end.max(start),
+            text: text.to_string(),
+        });
+    }
+
+    /// 清空记录 (开始新的识别时调用)
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// 所有记录
+    pub fn entries(&self) -> &[TranscriptEntry] {
+        &self.entries
+    }
+
+    /// 按指定格式导出
+    pub fn export(&self, format: TranscriptFormat, options: &WrapOptions) -> String {
+        match format {
+            TranscriptFormat::Srt => self.to_srt(options),
+            TranscriptFormat::Vtt => self.to_webvtt(options),
+            TranscriptFormat::Text => self.to_text(),
+        }
+    }
+
+    /// 导出为 SRT
+    pub fn to_srt(&self, options: &WrapOptions) -> String {
+        let mut output = String::new();
+        for (index, cue) in self.cues(options).iter().enumerate() {
+            output.push_str(&format!(
+                "{}\n{} --> {}\n{}\n\n",
+                index + 1,
+                format_timestamp(cue.start, ','),
+                format_timestamp(cue.end, ','),
+                cue.text
+            ));
+        }
+        output
+    }
+
+    /// 导出为 WebVTT
+    pub fn to_webvtt(&self, options: &WrapOptions) -> String {
+        let mut output = String::from("WEBVTT\n\n");
+        for cue in self.cues(options) {
+            output.push_str(&format!(
+                "{} --> {}\n{}\n\n",
+                format_timestamp(cue.start, '.'),
+                format_timestamp(cue.end, '.'),
+                escape_vtt(&cue.text)
+            ));
+        }
+        output
+    }
+
+    /// 导出为纯文本 (不换行，每句一行)
+    pub fn to_text(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| entry.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// 按换行规则把记录拆分为字幕条目
+    fn cues(&self, options: &WrapOptions) -> Vec<TranscriptEntry> {
+        self.entries
+            .iter()
+            .flat_map(|entry| split_entry(entry, options))
+            .collect()
+    }
+}
+
+/// 把一条记录按行数限制拆分为多条字幕，时间按文本宽度比例分配
+fn split_entry(entry: &TranscriptEntry, options: &WrapOptions) -> Vec<TranscriptEntry> {
+    let lines = wrap_text(&entry.text, options.max_line_width);
+    let chunks: Vec<&[String]> = lines.chunks(options.max_lines.max(1)).collect();
+
+    let widths: Vec<usize> = chunks
+        .iter()
+        .map(|chunk| chunk.iter().map(|line| text_width(line)).sum::<usize>())
+        .collect();
+    let total_width = widths.iter().sum::<usize>().max(1) as f32;
+    let duration = entry.end - entry.start;
+
+    let mut start = entry.start;
+    let mut consumed = 0;
+    chunks
+        .iter()
+        .zip(&widths)
+        .enumerate()
+        .map(|(index, (chunk, width))| {
+            consumed += width;
+            // 最后一条直接使用原结束时间，避免浮点误差
+            let end = if index + 1 == chunks.len() {
+                entry.end
+            } else {
+                entry.start + duration * consumed as f32 / total_width
+            };
+            let cue = TranscriptEntry {
+                start,
+                end,
+                text: chunk.join("\n"),
+            };
+            start = end;
+            cue
+        })
+        .collect()
+}
+
+/// 断行单位：一个拉丁单词或一个中日韩字符 (连同不能与其分开的标点)
+struct Word {
+    text: String,
+    /// 前面是否有空格
+    space_before: bool,
+}
+
+/// 按显示宽度换行
+pub fn wrap_text(text: &str, max_width: usize) -> Vec<String> {
+    let max_width = max_width.max(2);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut width = 0;
+
+    for word in split_words(text) {
+        let word_width = text_width(&word.text);
+        let mut separator = usize::from(word.space_before && !line.is_empty());
+
+        if !line.is_empty() && width + separator + word_width > max_width {
+            lines.push(std::mem::take(&mut line));
+            width = 0;
+            separator = 0;
+        }
+
+        if separator == 1 {
+            line.push(' ');
+            width += 1;
+        }
+
+        // 超过一行的长单词 (如 URL) 只能强制按字符断开
+        for c in word.text.chars() {
+            let char_width = char_width(c);
+            if !line.is_empty() && width + char_width > max_width {
+                lines.push(std::mem::take(&mut line));
+                width = 0;
+            }
+            line.push(c);
+            width += char_width;
+        }
+    }
+
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// 拆分断行单位
+fn split_words(text: &str) -> Vec<Word> {
+    let mut words: Vec<Word> = Vec::new();
+    let mut current = String::new();
+    let mut space_before = false;
+
+    let flush = |words: &mut Vec<Word>, current: &mut String, space_before: &mut bool| {
+        if !current.is_empty() {
+            words.push(Word {
+                text: std::mem::take(current),
+                space_before: *space_before,
+            });
+            *space_before = false;
+        }
+    };
+
+    for c in text.chars() {
+        if c.is_whitespace() {
+            flush(&mut words, &mut current, &mut space_before);
+            space_before = !words.is_empty();
+            continue;
+        }
+
+        // 句末标点不能出现在行首，附加到前一个单位
+        if is_closing_punctuation(c) {
+            if current.is_empty() && !space_before {
+                if let Some(last) = words.last_mut() {
+                    last.text.push(c);
+                    continue;
+                }
+            }
+            current.push(c);
+            continue;
+        }
+
+        // 开括号不能出现在行尾，与后面的字符合并
+        if is_opening_punctuation(c) {
+            if !current.chars().all(is_opening_punctuation) {
+                flush(&mut words, &mut current, &mut space_before);
+            }
+            current.push(c);
+            continue;
+        }
+
+        if is_cjk(c) {
+            if !current.chars().all(is_opening_punctuation) {
+                flush(&mut words, &mut current, &mut space_before);
+            }
+            current.push(c);
+            flush(&mut words, &mut current, &mut space_before);
+            continue;
+        }
+
+        // 拉丁单词紧跟在中日韩字符后面时允许断开
+        if current.chars().last().is_some_and(is_cjk) {
+            flush(&mut words, &mut current, &mut space_before);
+        }
+        current.push(c);
+    }
+
+    flush(&mut words, &mut current, &mut space_before);
+    words
+}
+
+/// 文本的显示宽度
+pub fn text_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+fn char_width(c: char) -> usize {
+    if is_cjk(c) {
+        2
+    } else {
+        1
+    }
+}
+
+/// 是否是全角 (中日韩) 字符
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1100..=0x115F       // 谚文字母
+            | 0x2E80..=0xA4CF // 中日韩部首、标点、假名、汉字
+            | 0xAC00..=0xD7A3 // 谚文音节
+            | 0xF900..=0xFAFF // 兼容汉字
+            | 0xFE30..=0xFE4F // 竖排标点
+            | 0xFF00..=0xFF60 // 全角字符
+            | 0xFFE0..=0xFFE6
+            | 0x20000..=0x3FFFD // 扩展汉字
+    )
+}
+
+fn is_closing_punctuation(c: char) -> bool {
+    ",.!?;:)]}%，。！？；：、）】」』》〉…".contains(c)
+}
+
+fn is_opening_punctuation(c: char) -> bool {
+    "([{（【「『《〈".contains(c)
+}
+
+/// 格式化时间为 `HH:MM:SS,mmm` (SRT) 或 `HH:MM:SS.mmm` (WebVTT)
+fn format_timestamp(seconds: f32, separator: char) -> String {
+    let millis = (seconds.max(0.0) as f64 * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        separator,
+        millis % 1000
+    )
+}
+
+/// WebVTT 中需要转义的字符
+fn escape_vtt(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0.0, ','), "00:00:00,000");
+        assert_eq!(format_timestamp(3725.5, ','), "01:02:05,500");
+        assert_eq!(format_timestamp(61.25, '.'), "00:01:01.250");
+    }
+
+    #[test]
+    fn test_wrap_latin_at_spaces() {
+        let lines = wrap_text("the quick brown fox jumps over the lazy dog", 16);
+        assert_eq!(
+            lines,
+            vec!["the quick brown", "fox jumps over", "the lazy dog"]
+        );
+        assert!(lines.iter().all(|line| text_width(line) <= 16));
+    }
+
+    #[test]
+    fn test_wrap_cjk_by_width() {
+        let lines = wrap_text("今天我们讨论实时字幕的导出功能。", 10);
+        assert_eq!(lines, vec!["今天我们讨", "论实时字幕", "的导出功", "能。"]);
+    }
+
+    #[test]
+    fn test_wrap_mixed_text() {
+        let lines = wrap_text("我们使用 sherpa-onnx 进行识别", 12);
+        assert_eq!(lines, vec!["我们使用", "sherpa-onnx", "进行识别"]);
+    }
+
+    #[test]
+    fn test_wrap_long_word() {
+        let lines = wrap_text("abcdefghij", 4);
+        assert_eq!(lines, vec!["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn test_srt_splits_long_entries() {
+        let mut transcript = Transcript::default();
+        transcript.push(1.0, 3.0, "hello world");
+        transcript.push(4.0, 8.0, "aaaa bbbb cccc dddd");
+        transcript.push(9.0, 9.5, "   ");
+
+        let options = WrapOptions {
+            max_line_width: 9,
+            max_lines: 1,
+        };
+        assert_eq!(
+            transcript.to_srt(&options),
+            "1\n00:00:01,000 --> 00:00:02,000\nhello\n\n\
+             2\n00:00:02,000 --> 00:00:03,000\nworld\n\n\
+             3\n00:00:04,000 --> 00:00:06,000\naaaa bbbb\n\n\
+             4\n00:00:06,000 --> 00:00:08,000\ncccc dddd\n\n"
+        );
+    }
+
+    #[test]
+    fn test_webvtt_and_text() {
+        let mut transcript = Transcript::default();
+        transcript.push(0.5, 2.0, "a < b");
+        transcript.push(2.5, 4.0, "第二句");
+
+        let vtt = transcript.to_webvtt(&WrapOptions::default());
+        assert!(vtt.starts_with("WEBVTT\n\n"));
+        assert!(vtt.contains("00:00:00.500 --> 00:00:02.000\na &lt; b\n"));
+        assert_eq!(transcript.to_text(), "a < b\n第二句");
+    }
+}
@@ -0,0 +1,505 @@
+//! 识别流程测试
+//!
+//! 用 `ScriptedRecognizer` 代替模型、用合成音频代替音频设备，
+//! 检查识别引擎输出的字幕事件序列 (中间结果去重、endpoint、重置与结束处理)。
+
+use live_subtitles_lib::audio_source::{
+    AudioReceiver, AudioSource, ChannelSource, Signal, SyntheticSource,
+};
+use live_subtitles_lib::engine::{Engine, EngineEvent};
+use live_subtitles_lib::online_asr::RecognitionResult;
+use live_subtitles_lib::recognizer::{LoadedRecognizer, StreamingRecognizer};
+use live_subtitles_lib::{AppError, RecognitionState};
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const SAMPLE_RATE: i32 = 16000;
+
+/// `ScriptedRecognizer` 的一步
+#[derive(Debug, Clone, PartialEq)]
+struct ScriptStep {
+    /// 当前语句的识别文本
+    text: String,
+    /// 是否检测到 endpoint
+    is_endpoint: bool,
+    /// 每个字的开始时间 (秒，相对语句开始；为空表示模型不支持)
+    timestamps: Vec<f32>,
+}
+
+impl ScriptStep {
+    /// 语句尚未结束
+    fn partial(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            is_endpoint: false,
+            timestamps: Vec::new(),
+        }
+    }
+
+    /// 检测到 endpoint
+    fn endpoint(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            is_endpoint: true,
+            timestamps: Vec::new(),
+        }
+    }
+
+    /// 带 token 时间
+    fn at(mut self, timestamps: &[f32]) -> Self {
+        self.timestamps = timestamps.to_vec();
+        self
+    }
+}
+
+/// 按脚本返回结果的流式识别器，不需要模型文件 (用于测试识别流程)
+///
+/// 每次 `process` 依次返回脚本中的一步 (与送入的音频无关)，脚本用完后返回空结果。
+/// 克隆的识别器共享脚本和计数，测试保留一份以便检查调用情况
+#[derive(Clone)]
+struct ScriptedRecognizer {
+    sample_rate: i32,
+    steps: Arc<Mutex<VecDeque<ScriptStep>>>,
+    resets: Arc<Mutex<usize>>,
+    hotwords: Arc<Mutex<Vec<String>>>,
+}
+
+impl ScriptedRecognizer {
+    fn new(sample_rate: i32, steps: impl IntoIterator<Item = ScriptStep>) -> Self {
+        Self {
+            sample_rate,
+            steps: Arc::new(Mutex::new(steps.into_iter().collect())),
+            resets: Arc::new(Mutex::new(0)),
+            hotwords: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// `reset` 被调用的次数
+    fn reset_count(&self) -> usize {
+        *self.resets.lock().unwrap()
+    }
+
+    /// 依次设置过的热词
+    fn hotwords(&self) -> Vec<String> {
+        self.hotwords.lock().unwrap().clone()
+    }
+}
+
+impl StreamingRecognizer for ScriptedRecognizer {
+    fn sample_rate(&self) -> i32 {
+        self.sample_rate
+    }
+
+    fn process(&mut self, _samples: &[f32]) -> (RecognitionResult, bool) {
+        let Some(step) = self.steps.lock().unwrap().pop_front() else {
+            return (RecognitionResult::default(), false);
+        };
+        let result = RecognitionResult {
+            tokens: step.text.chars().map(String::from).collect(),
+            text: step.text,
+            timestamps: step.timestamps,
+        };
+        (result, step.is_endpoint)
+    }
+
+    fn reset(&mut self) {
+        *self.resets.lock().unwrap() += 1;
+    }
+
+    fn set_hotwords(&mut self, hotwords: &str) {
+        self.hotwords.lock().unwrap().push(hotwords.to_string());
+    }
+}
+
+/// 送出一块音频后出错结束的音频源 (模拟文件读到一半无法解码)
+struct FailingSource;
+
+impl AudioSource for FailingSource {
+    fn start(&mut self, _sample_rate: u32) -> Result<AudioReceiver, AppError> {
+        let (tx, rx) = mpsc::channel();
+        tx.send(vec![0.0; 1600]).unwrap();
+        Ok(rx)
+    }
+
+    fn stop(&mut self) {}
+
+    fn describe(&self) -> String {
+        "failing".to_string()
+    }
+
+    fn take_error(&mut self) -> Option<AppError> {
+        Some(AppError::AudioFile {
+            path: "broken.mp3".into(),
+            message: "无法解码音频数据".to_string(),
+        })
+    }
+}
+
+/// 每次 `process` 对应合成音频的一块 (100ms)
+fn start(
+    engine: &Engine,
+    recognizer: &ScriptedRecognizer,
+    source: SyntheticSource,
+) -> Receiver<EngineEvent> {
+    let (tx, rx) = mpsc::channel();
+    let recognizer = LoadedRecognizer::Online(Box::new(recognizer.clone()));
+    engine
+        .start_with_recognizer(recognizer, source, tx)
+        .unwrap();
+    rx
+}
+
+/// 事件的简短描述，便于比较整个序列
+fn describe(event: &EngineEvent) -> String {
+    match event {
+        EngineEvent::Subtitle(subtitle) if subtitle.is_final => format!("final {}", subtitle.text),
+        EngineEvent::Subtitle(subtitle) => format!("partial {}", subtitle.text),
+        EngineEvent::State(state) => format!("state {:?}", state),
+        EngineEvent::Error(error) => format!("error {}", error),
+        EngineEvent::ModelLoading(loading) => format!("loading {}", loading),
+        EngineEvent::Finished => "finished".to_string(),
+    }
+}
+
+/// 接收下一个事件 (超时视为测试失败)
+fn next(rx: &Receiver<EngineEvent>) -> EngineEvent {
+    rx.recv_timeout(Duration::from_secs(5))
+        .expect("timed out waiting for engine event")
+}
+
+#[test]
+fn test_online_event_sequence() {
+    let recognizer = ScriptedRecognizer::new(
+        SAMPLE_RATE,
+        [
+            ScriptStep::partial("你"),
+            ScriptStep::partial("你好"),
+            // 文本未变化，不发送事件
+            ScriptStep::partial("你好"),
+            ScriptStep::endpoint("你好"),
+            // 没有文本的 endpoint 只重置
+            ScriptStep::endpoint(""),
+            ScriptStep::partial("世"),
+            ScriptStep::partial("世界"),
+        ],
+    );
+    let engine = Engine::new();
+    let source = SyntheticSource::silence(Duration::from_millis(800));
+    let events: Vec<EngineEvent> = start(&engine, &recognizer, source).iter().collect();
+
+    assert_eq!(
+        events.iter().map(describe).collect::<Vec<_>>(),
+        vec![
+            "state Loading",
+            "state Running",
+            "partial 你",
+            "partial 你好",
+            "final 你好",
+            "partial 世",
+            "partial 世界",
+            // 音频结束时未结束的语句作为最终结果
+            "final 世界",
+            "finished",
+            "state Idle",
+        ]
+    );
+
+    // 语句位置：endpoint 后从下一块音频开始计算
+    let times: Vec<(f32, f32)> = events
+        .iter()
+        .filter_map(|event| match event {
+            EngineEvent::Subtitle(subtitle) => Some((
+                (subtitle.start_time * 10.0).round() / 10.0,
+                (subtitle.end_time * 10.0).round() / 10.0,
+            )),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        times,
+        vec![
+            (0.0, 0.1),
+            (0.0, 0.2),
+            (0.0, 0.4),
+            (0.5, 0.6),
+            (0.5, 0.7),
+            (0.5, 0.8),
+        ]
+    );
+
+    // 开始识别时一次，两个 endpoint 各一次
+    assert_eq!(recognizer.reset_count(), 3);
+    assert_eq!(engine.state(), RecognitionState::Idle);
+}
+
+#[test]
+fn test_final_time_from_token_timestamps() {
+    let recognizer = ScriptedRecognizer::new(
+        SAMPLE_RATE,
+        [
+            ScriptStep::partial(""),
+            ScriptStep::partial(""),
+            ScriptStep::partial("你好").at(&[0.2, 0.25]),
+            ScriptStep::partial("你好").at(&[0.2, 0.25]),
+            ScriptStep::partial("你好").at(&[0.2, 0.25]),
+            ScriptStep::endpoint("你好").at(&[0.2, 0.25]),
+            ScriptStep::partial("世界").at(&[0.1, 0.3]),
+        ],
+    );
+    let engine = Engine::new();
+    let source = SyntheticSource::silence(Duration::from_millis(800));
+    let finals: Vec<_> = start(&engine, &recognizer, source)
+        .iter()
+        .filter_map(|event| match event {
+            EngineEvent::Subtitle(subtitle) if subtitle.is_final => Some(subtitle),
+            _ => None,
+        })
+        .collect();
+
+    let round = |t: f32| (t * 100.0).round() / 100.0;
+    let times: Vec<(f32, f32)> = finals
+        .iter()
+        .map(|subtitle| (round(subtitle.start_time), round(subtitle.end_time)))
+        .collect();
+    // 不包括第一个字之前的静音和 endpoint 前的静音；音频结束时不超过已送入的音频
+    assert_eq!(times, vec![(0.2, 0.55), (0.7, 0.8)]);
+    assert_eq!(
+        finals[1]
+            .token_timestamps
+            .iter()
+            .map(|t| round(*t))
+            .collect::<Vec<_>>(),
+        vec![0.7, 0.9]
+    );
+}
+
+#[test]
+fn test_final_event_tokens() {
+    let recognizer = ScriptedRecognizer::new(SAMPLE_RATE, [ScriptStep::endpoint("hi")]);
+    let engine = Engine::new();
+    let source = SyntheticSource::tone(440.0, 0.5, Duration::from_millis(100));
+
+    let finals: Vec<_> = start(&engine, &recognizer, source)
+        .iter()
+        .filter_map(|event| match event {
+            EngineEvent::Subtitle(subtitle) if subtitle.is_final => Some(subtitle),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(finals.len(), 1);
+    assert_eq!(finals[0].tokens, vec!["h", "i"]);
+}
+
+#[test]
+fn test_stop_discards_pending_text() {
+    let recognizer = ScriptedRecognizer::new(SAMPLE_RATE, [ScriptStep::partial("还没说完")]);
+    let engine = Engine::new();
+    let rx = start(
+        &engine,
+        &recognizer,
+        SyntheticSource::new(Signal::Silence, None),
+    );
+
+    assert_eq!(describe(&next(&rx)), "state Loading");
+    assert_eq!(describe(&next(&rx)), "state Running");
+    assert_eq!(describe(&next(&rx)), "partial 还没说完");
+
+    engine.stop().unwrap();
+    let rest: Vec<String> = rx.iter().map(|event| describe(&event)).collect();
+    assert_eq!(rest, vec!["state Idle"]);
+    assert!(!engine.is_active());
+}
+
+#[test]
+fn test_restart_after_stop() {
+    let first = ScriptedRecognizer::new(SAMPLE_RATE, [ScriptStep::partial("第一次")]);
+    let engine = Engine::new();
+    let rx = start(&engine, &first, SyntheticSource::new(Signal::Silence, None));
+
+    assert_eq!(describe(&next(&rx)), "state Loading");
+    assert_eq!(describe(&next(&rx)), "state Running");
+    assert_eq!(describe(&next(&rx)), "partial 第一次");
+
+    // 停止后立即开始新的识别：旧的识别线程已退出，不会结束新的会话
+    engine.stop().unwrap();
+    let second = ScriptedRecognizer::new(SAMPLE_RATE, [ScriptStep::partial("第二次")]);
+    let rx2 = start(
+        &engine,
+        &second,
+        SyntheticSource::new(Signal::Silence, None),
+    );
+    let rest: Vec<String> = rx.iter().map(|event| describe(&event)).collect();
+    assert_eq!(rest, vec!["state Idle"]);
+
+    assert_eq!(describe(&next(&rx2)), "state Loading");
+    assert_eq!(describe(&next(&rx2)), "state Running");
+    assert_eq!(describe(&next(&rx2)), "partial 第二次");
+    assert_eq!(engine.state(), RecognitionState::Running);
+    assert_eq!(first.reset_count(), 1);
+
+    engine.stop().unwrap();
+    let rest: Vec<String> = rx2.iter().map(|event| describe(&event)).collect();
+    assert_eq!(rest, vec!["state Idle"]);
+}
+
+#[test]
+fn test_finish_flushes_pending_text() {
+    let recognizer = ScriptedRecognizer::new(SAMPLE_RATE, [ScriptStep::partial("最后一句")]);
+    let engine = Engine::new();
+    let rx = start(
+        &engine,
+        &recognizer,
+        SyntheticSource::new(Signal::Silence, None),
+    );
+
+    assert_eq!(describe(&next(&rx)), "state Loading");
+    assert_eq!(describe(&next(&rx)), "state Running");
+    assert_eq!(describe(&next(&rx)), "partial 最后一句");
+
+    engine.finish();
+    let rest: Vec<String> = rx.iter().map(|event| describe(&event)).collect();
+    assert_eq!(rest, vec!["final 最后一句", "finished", "state Idle"]);
+}
+
+#[test]
+fn test_source_error_ends_with_error() {
+    let recognizer = ScriptedRecognizer::new(SAMPLE_RATE, [ScriptStep::partial("一")]);
+    let engine = Engine::new();
+    let (tx, rx) = mpsc::channel();
+    let loaded = LoadedRecognizer::Online(Box::new(recognizer.clone()));
+    engine
+        .start_with_recognizer(loaded, FailingSource, tx)
+        .unwrap();
+
+    // 已识别的文本照常输出，但不报告正常结束
+    let events: Vec<String> = rx.iter().map(|event| describe(&event)).collect();
+    assert_eq!(
+        events,
+        vec![
+            "state Loading",
+            "state Running",
+            "partial 一",
+            "final 一",
+            "error 无法解码音频数据",
+            "state Error(\"无法解码音频数据\")",
+        ]
+    );
+    assert!(matches!(engine.state(), RecognitionState::Error(_)));
+}
+
+#[test]
+fn test_pause_drops_audio() {
+    let recognizer = ScriptedRecognizer::new(
+        SAMPLE_RATE,
+        [ScriptStep::partial("一"), ScriptStep::partial("一二")],
+    );
+    let engine = Engine::new();
+    // 没有缓冲的通道：发送完成时识别线程已取走这块音频
+    let (audio_tx, audio_rx) = mpsc::sync_channel(0);
+    let (tx, rx) = mpsc::channel();
+    let loaded = LoadedRecognizer::Online(Box::new(recognizer.clone()));
+    engine
+        .start_with_recognizer(loaded, ChannelSource::new(audio_rx), tx)
+        .unwrap();
+
+    assert_eq!(describe(&next(&rx)), "state Loading");
+    assert_eq!(describe(&next(&rx)), "state Running");
+    audio_tx.send(vec![0.0; 1600]).unwrap();
+    assert_eq!(describe(&next(&rx)), "partial 一");
+
+    // 暂停期间的音频不送入识别器 (不消耗脚本)，但计入音频位置
+    engine.pause().unwrap();
+    assert_eq!(describe(&next(&rx)), "state Paused");
+    assert!(engine.pause().is_err());
+    audio_tx.send(vec![0.0; 1600]).unwrap();
+    // 只有一个样本，保证上一块已在暂停时处理 (这块在恢复前后处理都不影响时间)
+    audio_tx.send(vec![0.0; 1]).unwrap();
+
+    // 恢复后暂停前的语句作为最终结果，新的语句从暂停后的位置开始
+    engine.resume().unwrap();
+    assert_eq!(describe(&next(&rx)), "state Running");
+    audio_tx.send(vec![0.0; 1600]).unwrap();
+    let round = |t: f32| (t * 10.0).round() / 10.0;
+    let times = |event: &EngineEvent| match event {
+        EngineEvent::Subtitle(subtitle) => (round(subtitle.start_time), round(subtitle.end_time)),
+        _ => panic!("expected subtitle, got {:?}", event),
+    };
+    let event = next(&rx);
+    assert_eq!(describe(&event), "final 一");
+    assert_eq!(times(&event), (0.0, 0.1));
+    let event = next(&rx);
+    assert_eq!(describe(&event), "partial 一二");
+    assert_eq!(times(&event).0, 0.2);
+
+    // 音频源在暂停期间结束，同样输出未结束的语句
+    engine.pause().unwrap();
+    assert_eq!(describe(&next(&rx)), "state Paused");
+    audio_tx.send(vec![0.0; 1600]).unwrap();
+    drop(audio_tx);
+    let rest: Vec<EngineEvent> = rx.iter().collect();
+    assert_eq!(
+        rest.iter().map(describe).collect::<Vec<_>>(),
+        vec!["final 一二", "finished", "state Idle"]
+    );
+    assert_eq!(times(&rest[0]).0, 0.2);
+}
+
+#[test]
+fn test_hotwords_update_between_utterances() {
+    let recognizer = ScriptedRecognizer::new(
+        SAMPLE_RATE,
+        [
+            ScriptStep::partial("一"),
+            ScriptStep::endpoint("一"),
+            ScriptStep::partial("二"),
+        ],
+    );
+    let engine = Engine::new();
+    engine.set_hotwords("语音识别 :2".to_string());
+    let (audio_tx, audio_rx) = mpsc::channel();
+    let (tx, rx) = mpsc::channel();
+    let loaded = LoadedRecognizer::Online(Box::new(recognizer.clone()));
+    engine
+        .start_with_recognizer(loaded, ChannelSource::new(audio_rx), tx)
+        .unwrap();
+
+    assert_eq!(describe(&next(&rx)), "state Loading");
+    assert_eq!(describe(&next(&rx)), "state Running");
+    audio_tx.send(vec![0.0; 1600]).unwrap();
+    assert_eq!(describe(&next(&rx)), "partial 一");
+
+    // 语句进行中修改热词，到 endpoint 之后才更换
+    engine.set_hotwords("字幕 :1.5".to_string());
+    assert_eq!(recognizer.hotwords(), vec!["语音识别 :2"]);
+    audio_tx.send(vec![0.0; 1600]).unwrap();
+    assert_eq!(describe(&next(&rx)), "final 一");
+    audio_tx.send(vec![0.0; 1600]).unwrap();
+    assert_eq!(describe(&next(&rx)), "partial 二");
+    assert_eq!(recognizer.hotwords(), vec!["语音识别 :2", "字幕 :1.5"]);
+
+    engine.stop().unwrap();
+}
+
+#[test]
+fn test_start_while_running_fails() {
+    let recognizer = ScriptedRecognizer::new(SAMPLE_RATE, []);
+    let engine = Engine::new();
+    let _rx = start(
+        &engine,
+        &recognizer,
+        SyntheticSource::new(Signal::Silence, None),
+    );
+
+    let second = ScriptedRecognizer::new(SAMPLE_RATE, []);
+    let (tx, _) = mpsc::channel();
+    let loaded = LoadedRecognizer::Online(Box::new(second));
+    assert!(engine
+        .start_with_recognizer(loaded, SyntheticSource::silence(Duration::ZERO), tx)
+        .is_err());
+
+    engine.stop().unwrap();
+    assert_eq!(engine.state(), RecognitionState::Idle);
+}
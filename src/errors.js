@@ -0,0 +1,43 @@
+// 后端命令的错误为 { code, message, details }（见 src-tauri/src/error.rs），
+// 根据 code 在错误信息后附上解决办法
+
+const HINTS = {
+    config: "请检查设置中的参数",
+    model_not_configured: "请在设置中选择模型",
+    audio_device: "请在设置中选择其他音频设备",
+    audio_file: "请确认文件存在且格式受支持（WAV/FLAC/MP3/OGG）",
+    recognizer: "模型文件可能已损坏或与模型类型不匹配",
+    server: "请更换字幕推送服务的端口或监听地址",
+};
+
+/**
+ * 把 invoke 抛出的错误转换为显示给用户的文本
+ * 兼容字符串和 Error 对象（前端自身的错误）
+ */
+export function formatError(e) {
+    if (e && typeof e === "object" && "code" in e) {
+        if (e.code === "model_files_missing") {
+            return `${e.message}，请检查模型目录 ${e.details.model_dir} 或重新扫描模型`;
+        }
+        if (e.code === "model_files_invalid") {
+            return `${e.message}，可在设置中点击“检查模型文件”查看详情`;
+        }
+        const hint = HINTS[e.code];
+        return hint ? `${e.message}，${hint}` : e.message;
+    }
+    return e instanceof Error ? e.message : String(e);
+}
+
+// validate_model 返回的文件问题（与 src-tauri/src/model_validation.rs 的 FileProblem 对应）
+const FILE_PROBLEMS = {
+    not_configured: "未指定文件",
+    missing: "文件不存在",
+    unreadable: "无法读取",
+    invalid_format: "格式错误",
+    mismatch: "与配置不一致",
+};
+
+export function describeFileProblem(problem) {
+    const text = FILE_PROBLEMS[problem.kind] ?? problem.kind;
+    return problem.message ? `${text}: ${problem.message}` : text;
+}
@@ -1,6 +1,19 @@
 import { defineStore } from 'pinia'
 import { ref, computed } from 'vue'
 
+// 识别参数默认值（与后端 RecognizerOptions::default 一致）
+export const DEFAULT_RECOGNIZER_OPTIONS = {
+    feature_dim: 80,
+    enable_endpoint: true,
+    rule1_min_trailing_silence: 2.4,
+    rule2_min_trailing_silence: 1.2,
+    rule3_min_utterance_length: 20.0,
+    decoding_method: 'greedy_search',
+    max_active_paths: 4,
+    modeling_unit: 'cjkchar',
+    bpe_vocab: '',
+}
+
 /**
  * 设置 Store
  * 用于管理应用的所有配置，支持持久化和导入导出
@@ -27,6 +40,7 @@ export const useSettingsStore = defineStore('settings', () => {
     // ========== 音频设置 ==========
     const audioSourceType = ref('systemaudio') // 音频源类型: 'systemaudio' 或 'microphone'
     const audioDeviceId = ref('') // 已废弃，保留用于兼容
+    const audioDeviceIdForSystem = ref('') // 系统音频（输出）设备ID
     const audioDeviceIdForMicrophone = ref('') // 麦克风设备ID
     const availableAudioDevices = ref([]) // 可用的音频设备列表
 
@@ -35,6 +49,7 @@ export const useSettingsStore = defineStore('settings', () => {
     const currentModelId = ref('') // 当前选中的模型 ID（目录名）
     const availableModels = ref([]) // 可用的模型列表 [{id, name, encoder, decoder, joiner, tokens, isComplete}]
     const modelAdvancedConfig = ref({}) // 高级配置：每个模型的手动覆盖配置 {modelId: {encoder, decoder, joiner, tokens}}
+    const modelRecognizerOptions = ref({}) // 识别参数：每个模型的 endpoint 规则与解码选项 {modelId: {...}}
 
     // ========== 后续可添加更多设置 ==========
     // 例如：
@@ -56,6 +71,7 @@ export const useSettingsStore = defineStore('settings', () => {
                 encoder: advancedConfig.encoder || model.encoder,
                 decoder: advancedConfig.decoder || model.decoder,
                 joiner: advancedConfig.joiner || model.joiner,
+                model: advancedConfig.model || model.model,
                 tokens: advancedConfig.tokens || model.tokens,
             }
         }
@@ -88,6 +104,7 @@ export const useSettingsStore = defineStore('settings', () => {
             modelsRootDir: modelsRootDir.value,
             currentModelId: currentModelId.value,
             modelAdvancedConfig: modelAdvancedConfig.value,
+            modelRecognizerOptions: modelRecognizerOptions.value,
         },
     }))
 
@@ -153,6 +170,77 @@ export const useSettingsStore = defineStore('settings', () => {
         }
     }
 
+    /**
+     * 根据扫描到的模型种类构建后端的 model_type 配置
+     */
+    function buildModelType(model) {
+        // model.json 声明了类型时以其为准，只替换选择的版本或手动修改的文件
+        if (model.model_type) {
+            // 选择的版本带有完整的类型 (包括 HLG 解码图和语言)，以文件判断选择的是哪个版本
+            const variant = model.variants?.find(v => v.model_type &&
+                ['encoder', 'decoder', 'joiner', 'model'].every(field => !v[field] || v[field] === model[field]))
+            const modelType = { ...(variant?.model_type || model.model_type) }
+            for (const field of ['encoder', 'decoder', 'joiner', 'model']) {
+                if (field in modelType && model[field]) {
+                    modelType[field] = model[field]
+                }
+            }
+            return modelType
+        }
+        if (model.model_kind === 'zipformer2_ctc' || model.model_kind === 'nemo_ctc') {
+            return {
+                type: model.model_kind === 'nemo_ctc' ? "NemoCtc" : "Zipformer2Ctc",
+                model: model.model || "",
+                hlg_graph: model.hlg_graph || null,
+            }
+        }
+        if (model.model_kind === 'sense_voice') {
+            return {
+                type: "SenseVoice",
+                model: model.model || "",
+                language: "",
+            }
+        }
+        if (model.model_kind === 'whisper') {
+            return {
+                type: "Whisper",
+                encoder: model.encoder || "",
+                decoder: model.decoder || "",
+                language: "",
+            }
+        }
+        if (model.model_kind === 'paraformer') {
+            return {
+                type: "Paraformer",
+                encoder: model.encoder || "",
+                decoder: model.decoder || "",
+            }
+        }
+        return {
+            type: "Transducer",
+            encoder: model.encoder || "",
+            decoder: model.decoder || "",
+            joiner: model.joiner || "",
+        }
+    }
+
+    /**
+     * 构建后端的模型配置 (语言和采样率以 model.json 声明的为准)
+     */
+    function buildModelConfig(model) {
+        return {
+            id: model.id,
+            name: model.model_name,
+            model_dir: model.model_dir,
+            model_type: buildModelType(model),
+            tokens: model.tokens || "",
+            languages: model.languages?.length ? model.languages : ["zh", "en"],
+            sample_rate: model.sample_rate || 16000,
+            num_threads: 2,
+            options: getRecognizerOptions(model.id),
+        }
+    }
+
     /**
      * 设置可用音频设备列表
      */
@@ -173,6 +261,9 @@ export const useSettingsStore = defineStore('settings', () => {
         if (settings.modelAdvancedConfig !== undefined) {
             modelAdvancedConfig.value = settings.modelAdvancedConfig
         }
+        if (settings.modelRecognizerOptions !== undefined) {
+            modelRecognizerOptions.value = settings.modelRecognizerOptions
+        }
     }
 
     /**
@@ -182,7 +273,7 @@ export const useSettingsStore = defineStore('settings', () => {
         availableModels.value = models
         // 如果当前选中的模型不在列表中，自动选择第一个完整的模型
         if (!models.find(m => m.id === currentModelId.value)) {
-            const firstComplete = models.find(m => m.isComplete)
+            const firstComplete = models.find(m => m.is_complete)
             if (firstComplete) {
                 currentModelId.value = firstComplete.id
             } else if (models.length > 0) {
@@ -203,6 +294,37 @@ export const useSettingsStore = defineStore('settings', () => {
         }
     }
 
+    /**
+     * 获取模型的默认识别参数（model.json 推荐的参数优先）
+     */
+    function getDefaultRecognizerOptions(modelId) {
+        const model = availableModels.value.find(m => m.id === modelId)
+        return {
+            ...DEFAULT_RECOGNIZER_OPTIONS,
+            ...(model?.options || {}),
+        }
+    }
+
+    /**
+     * 获取模型的识别参数（未设置的字段使用默认值）
+     */
+    function getRecognizerOptions(modelId) {
+        return {
+            ...getDefaultRecognizerOptions(modelId),
+            ...(modelRecognizerOptions.value[modelId] || {}),
+        }
+    }
+
+    /**
+     * 设置模型的识别参数
+     */
+    function setRecognizerOptions(modelId, options) {
+        modelRecognizerOptions.value = {
+            ...modelRecognizerOptions.value,
+            [modelId]: options
+        }
+    }
+
     /**
      * 清除所有模型相关配置（当模型根目录变化时调用）
      */
@@ -210,6 +332,7 @@ export const useSettingsStore = defineStore('settings', () => {
         availableModels.value = []
         currentModelId.value = ''
         modelAdvancedConfig.value = {}
+        modelRecognizerOptions.value = {}
     }
 
     /**
@@ -232,6 +355,7 @@ export const useSettingsStore = defineStore('settings', () => {
                 encoder: advancedConfig.encoder || model.encoder,
                 decoder: advancedConfig.decoder || model.decoder,
                 joiner: advancedConfig.joiner || model.joiner,
+                model: advancedConfig.model || model.model,
                 tokens: advancedConfig.tokens || model.tokens,
             };
         }
@@ -312,12 +436,14 @@ export const useSettingsStore = defineStore('settings', () => {
         proxyPassword.value = ''
         audioSourceType.value = 'systemaudio'
         audioDeviceId.value = ''
+        audioDeviceIdForSystem.value = ''
         audioDeviceIdForMicrophone.value = ''
         availableAudioDevices.value = []
         modelsRootDir.value = ''
         currentModelId.value = ''
         availableModels.value = []
         modelAdvancedConfig.value = {}
+        modelRecognizerOptions.value = {}
     }
 
     return {
@@ -333,12 +459,14 @@ export const useSettingsStore = defineStore('settings', () => {
         proxyPassword,
         audioSourceType,
         audioDeviceId,
+        audioDeviceIdForSystem,
         audioDeviceIdForMicrophone,
         availableAudioDevices,
         modelsRootDir,
         currentModelId,
         availableModels,
         modelAdvancedConfig,
+        modelRecognizerOptions,
 
         // 计算属性
         currentModel,
@@ -354,8 +482,13 @@ export const useSettingsStore = defineStore('settings', () => {
         setAvailableModels,
         setAvailableAudioDevices,
         setModelAdvancedConfig,
+        getDefaultRecognizerOptions,
+        getRecognizerOptions,
+        setRecognizerOptions,
         clearModelConfigs,
         getCurrentModelSync,
+        buildModelType,
+        buildModelConfig,
         exportSettings,
         importSettings,
         resetToDefaults,
@@ -378,11 +511,13 @@ export const useSettingsStore = defineStore('settings', () => {
             'proxyPassword',
             'audioSourceType',
             'audioDeviceId',
+            'audioDeviceIdForSystem',
             'audioDeviceIdForMicrophone',
             'modelsRootDir',
             'currentModelId',
             'availableModels',
-            'modelAdvancedConfig'
+            'modelAdvancedConfig',
+            'modelRecognizerOptions'
         ],
     },
 })